@@ -0,0 +1,13 @@
+use std::time::{Duration, Instant};
+
+pub fn bench(name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    while start.elapsed() < Duration::from_millis(500) {
+        f();
+        iterations += 1;
+    }
+
+    let elapsed = start.elapsed() / iterations;
+    println!("{name:<48} {:>12} ns/iter", elapsed.as_nanos());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use std::hint::black_box;
+
+use ari::{natural::Natural, parser::parser};
+use chumsky::Parser;
+use common::bench;
+use num_bigint::BigUint;
+use num_traits::Num;
+
+fn main() {
+    for digits in ["255", "18446744069414584320", &"1234567890".repeat(20)] {
+        let digit_values = digits.bytes().map(|c| c - b'0').collect::<Vec<_>>();
+        bench(&format!("from_str_radix ({} digits)", digits.len()), || {
+            let digits = black_box(digits).to_owned();
+            black_box(Natural::from(BigUint::from_str_radix(&digits, 10).unwrap()));
+        });
+
+        bench(&format!("from_digits ({} digits)", digits.len()), || {
+            black_box(Natural::from_digits(10, black_box(&digit_values)).unwrap());
+        });
+    }
+
+    let masks = (0..10_000)
+        .map(|i| format!(":mask{i} 0xFFFF_FFFF_{:04X}_0000", i % 0x10000))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    bench("parse 10000 hex masks", || {
+        black_box(parser().parse(black_box(masks.as_str())).unwrap());
+    });
+
+    let decimals = (0..10_000)
+        .map(|i| format!("{}", 1_000_000_007u64 * i))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    bench("parse 10000 decimals", || {
+        black_box(parser().parse(black_box(decimals.as_str())).unwrap());
+    });
+}
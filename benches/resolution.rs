@@ -0,0 +1,100 @@
+mod common;
+
+use std::hint::black_box;
+
+use ari::{
+    ast::{Expr, Label, Scope},
+    parser::parser,
+    prelude::prelude,
+};
+use chumsky::Parser;
+use common::bench;
+
+/// Nested scopes where every level refers to the outermost label, so
+/// each reference stays unresolved until the root.
+fn nested(depth: usize) -> Scope {
+    let mut scope = Scope::default();
+    for level in (0..depth).rev() {
+        scope = Scope::try_from_exprs([
+            Expr::natural([Label::new(0..0, format!("l{level}"))], 0..0, 8u8),
+            Expr::unresolved_symbol([], 0..0, "l0"),
+            Expr::unresolved_symbol([], 0..0, format!("l{}", level.saturating_sub(1))),
+            Expr::unresolved_symbol([], 0..0, "*"),
+            Expr::sexpr([], 0..0, scope),
+        ])
+        .unwrap_or_else(|(_err, scope)| scope);
+    }
+
+    scope
+}
+
+/// Nested scopes where the innermost scope refers to `depth` distinct
+/// labels of the outermost scope.
+fn deep_references(depth: usize) -> Scope {
+    let mut scope = Scope::try_from_exprs(
+        (0..depth).map(|i| Expr::unresolved_symbol([], 0..0, format!("s{i}"))),
+    )
+    .unwrap();
+
+    for _ in 1..depth {
+        scope = Scope::try_from_exprs([Expr::sexpr([], 0..0, scope)]).unwrap();
+    }
+
+    Scope::try_from_exprs(
+        (0..depth)
+            .map(|i| Expr::natural([Label::new(0..0, format!("s{i}"))], 0..0, 8u8))
+            .chain([Expr::sexpr([], 0..0, scope)]),
+    )
+    .unwrap()
+}
+
+/// Many sibling scopes referring to each other's labels.
+fn wide(width: usize) -> Scope {
+    Scope::try_from_exprs((0..width).map(|i| {
+        Expr::sexpr(
+            [Label::new(0..0, format!("f{i}"))],
+            0..0,
+            Scope::try_from_exprs([
+                Expr::unresolved_symbol([], 0..0, "*"),
+                Expr::unresolved_symbol([], 0..0, format!("f{}", i * 7 % width)),
+                Expr::unresolved_symbol([], 0..0, format!("f{}", (i + 1) % width)),
+            ])
+            .unwrap_or_else(|(_err, scope)| scope),
+        )
+    }))
+    .unwrap()
+}
+
+fn main() {
+    let prelude = prelude();
+
+    for depth in [10, 100, 1000] {
+        bench(&format!("resolve nested (depth {depth})"), || {
+            let mut scope = nested(black_box(depth));
+            scope.resolve_in(&prelude, &mut |_| ());
+            black_box(scope);
+        });
+    }
+
+    for depth in [10, 100, 1000] {
+        bench(&format!("resolve deep references (depth {depth})"), || {
+            black_box(deep_references(black_box(depth)));
+        });
+    }
+
+    for width in [100, 1000, 10_000] {
+        bench(&format!("resolve wide (width {width})"), || {
+            let mut scope = wide(black_box(width));
+            scope.resolve_in(&prelude, &mut |_| ());
+            black_box(scope);
+        });
+    }
+
+    let source = (0..100).fold(String::from(":x 1"), |inner, level| {
+        format!(":l{level} 8 (* ascii l{level} ({inner}))")
+    });
+
+    bench("parse nested (depth 100)", || {
+        black_box(parser().parse(black_box(source.as_str())).unwrap());
+    });
+}
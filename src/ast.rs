@@ -1,28 +1,69 @@
-use std::{
-    cell::{Cell, RefCell},
-    collections::{hash_map, HashMap},
-    fmt,
-    ops::Range,
-    vec::IntoIter,
-};
+use std::{collections::HashMap, fmt, mem, ops::Range, sync::Arc, vec::IntoIter};
 
-use crate::natural::Natural;
+use crate::{intern::Name, natural::Natural, prelude::Builtin, radix::RadixTree, source::FileId};
 
 /// A collection of labelled expressions where all references have
 /// been resolved by matching labels introduced in the scope.
 ///
-/// Acts as the root element for Ari's "abstract syntax tree".
-#[derive(Default)]
+/// Acts as the root element for Ari's "abstract syntax tree". Scopes
+/// are immutable once built, so clones share the same expressions.
+#[derive(Default, Clone)]
 pub struct Scope {
+    inner: Arc<ScopeInner>,
+}
+
+#[derive(Default, Clone)]
+struct ScopeInner {
     exprs: Box<[Expr]>,
-    expr_from_label: HashMap<String, (usize, usize)>,
+    expr_from_label: HashMap<Name, (usize, usize)>,
+    unresolved: Unresolved,
+}
+
+/// The references that a scope couldn't resolve, by symbol.
+///
+/// A scope takes over the map of its nested scope with the most
+/// symbols and only moves the references of its other expressions into
+/// it, so each reference is moved a logarithmic number of times no
+/// matter how deeply it's nested.
+#[derive(Default, Clone)]
+struct Unresolved {
+    /// The indices of the expressions leading from the scope that owns
+    /// the map to the scope it was built in, from innermost to
+    /// outermost.
+    trail: Vec<usize>,
+
+    /// Keyed by the names of the symbols, which share their prefixes.
+    symbols: RadixTree<Vec<Pending>>,
+}
+
+/// References to a symbol that skip the same number of labels.
+#[derive(Clone)]
+struct Pending {
+    /// The number of labels matching the symbol to
+    /// [skip](UnresolvedReference::skip).
+    skip: usize,
 
-    // TODO: Redesign `unresolved_map` as a radix tree
-    #[allow(clippy::type_complexity)]
-    unresolved_map: Cell<Option<HashMap<String, Vec<(usize, *mut ReferenceVariant)>>>>,
+    /// The length of the [trail](Unresolved::trail) when these were
+    /// added, so the occurrences are within the scope reached by
+    /// following the rest of it.
+    depth: usize,
+    occurrences: Occurrences,
+}
+
+/// Where references are, by the index of the expression containing
+/// them. Nesting mirrors the s-expressions.
+type Occurrences = Vec<(usize, Occurrence)>;
+
+#[derive(Clone)]
+enum Occurrence {
+    /// The expression is the reference, possibly dereferenced.
+    Reference,
+    /// The expression is an s-expression containing references.
+    SExpr(Occurrences),
 }
 
 impl Scope {
+    #[allow(clippy::result_large_err)]
     pub fn try_from_exprs(
         iter: impl IntoIterator<Item = Expr>,
     ) -> Result<Scope, (Box<[ScopeError]>, Scope)> {
@@ -41,7 +82,7 @@ impl Scope {
     ) -> Self {
         let iter = iter.into_iter();
         let mut exprs = Vec::with_capacity(iter.size_hint().0);
-        let mut expr_from_label: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut expr_from_label: HashMap<Name, (usize, usize)> = HashMap::new();
         for (index, expr) in iter.enumerate() {
             exprs.push(expr);
             let expr = &exprs[index];
@@ -59,119 +100,494 @@ impl Scope {
             }
         }
 
-        let mut unresolved_map: HashMap<String, Vec<(usize, *mut ReferenceVariant)>> =
-            HashMap::new();
-
-        for (index, expr) in exprs.iter().enumerate() {
-            match &expr.base.variant {
-                ExprVariant::Natural(_natural) => (),
-                ExprVariant::Reference(reference) => {
-                    let resolved = match &*reference.cell.borrow() {
-                        ReferenceVariant::Unresolved(unresolved) => {
-                            let symbol = &unresolved.symbol.name;
-                            if let Some((other_index, _)) = expr_from_label.get(symbol).copied() {
-                                let other_expr = &exprs[other_index];
-                                match other_expr.base.resolve_path(unresolved.path.as_ref()) {
-                                    Ok(path) => {
-                                        Some(ReferenceVariant::Resolved(ResolvedReference {
-                                            scope: 0,
-                                            offset: other_index as isize - index as isize,
-                                            path,
-                                        }))
-                                    }
-                                    Err(path) => {
-                                        emit(ScopeError::InvalidPath(path_span(path)));
-                                        None
-                                    }
-                                }
-                            } else {
-                                // Don't need to handle when symbol is already in unresolved_map, since
-                                // we can't have duplicate labels in the same scope
-                                unresolved_map
-                                    .insert(symbol.clone(), vec![(1, reference.cell.as_ptr())]);
-
-                                None
-                            }
-                        }
-                        ReferenceVariant::Resolved(_path) => None,
+        // Take over the biggest map, so only the smaller ones are moved
+        let mut unresolved = Unresolved::default();
+        let biggest = (0..exprs.len()).max_by_key(|&index| exprs[index].base.unresolved_len());
+        if let Some(index) = biggest.filter(|&index| exprs[index].base.unresolved_len() > 0) {
+            if let ExprVariant::SExpr(scope) = &mut exprs[index].base.unwrap_derefs_mut().variant {
+                unresolved = mem::take(&mut Arc::make_mut(&mut scope.inner).unresolved);
+                unresolved.trail.push(index);
+            }
+        }
+
+        for (index, expr) in exprs.iter_mut().enumerate() {
+            expr.base.take_unresolved(index, &mut unresolved);
+        }
+
+        let pending = unresolved.take_labelled(&expr_from_label);
+        let mut resolved = Vec::new();
+        resolve_pending(
+            &exprs,
+            &unresolved.trail,
+            &pending,
+            (&exprs, None),
+            &mut resolved,
+            emit,
+        );
+
+        patch_pending(
+            &mut exprs,
+            &unresolved.trail,
+            &pending,
+            &mut resolved.into_iter(),
+        );
+
+        Self {
+            inner: Arc::new(ScopeInner {
+                exprs: exprs.into_boxed_slice(),
+                expr_from_label,
+                unresolved,
+            }),
+        }
+    }
+
+    pub fn exprs(&self) -> &[Expr] {
+        &self.inner.exprs
+    }
+
+    /// Checks if two scopes have the same structure, wherever their
+    /// expressions are in the source.
+    pub fn eq_ignoring_spans(&self, other: &Self) -> bool {
+        self.exprs().len() == other.exprs().len()
+            && self
+                .exprs()
+                .iter()
+                .zip(other.exprs())
+                .all(|(expr, other)| expr.eq_ignoring_spans(other))
+    }
+
+    /// The index of the expression labelled `name`.
+    pub fn index_of(&self, name: &Name) -> Option<usize> {
+        self.inner
+            .expr_from_label
+            .get(name)
+            .map(|(index, _)| *index)
+    }
+
+    /// Resolves the remaining references against an outer scope (like
+    /// the [prelude](crate::prelude::prelude)), as if this scope was an
+    /// expression placed right after the outer scope's expressions.
+    pub fn resolve_in(&mut self, outer: &Scope, emit: &mut dyn FnMut(ScopeError)) {
+        let inner = Arc::make_mut(&mut self.inner);
+        let pending = inner.unresolved.take_labelled(&outer.inner.expr_from_label);
+        let mut resolved = Vec::new();
+        resolve_pending(
+            &inner.exprs,
+            &inner.unresolved.trail,
+            &pending,
+            (&outer.inner.exprs, Some(outer.inner.exprs.len())),
+            &mut resolved,
+            emit,
+        );
+
+        patch_pending(
+            &mut inner.exprs,
+            &inner.unresolved.trail,
+            &pending,
+            &mut resolved.into_iter(),
+        );
+    }
+
+    /// Nests a root scope that was resolved against an outer scope
+    /// inside another scope, so references to the outer scope still
+    /// resolve. References that are still unresolved stay unresolved,
+    /// rather than resolving against their new parent.
+    pub fn nest(mut self) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.unresolved = Unresolved::default();
+        shift_outer_references(&mut inner.exprs, 0);
+        self
+    }
+
+    /// Reports every reference that's still unresolved after resolving
+    /// against an outer scope, suggesting similar labels that are in
+    /// scope.
+    pub fn close(&self, outer: &Scope, emit: &mut dyn FnMut(ScopeError)) {
+        let mut errors = self
+            .inner
+            .unresolved
+            .locations()
+            .into_iter()
+            .map(|indices| {
+                // Every scope enclosing the reference, from outermost to innermost
+                let mut scopes = vec![outer, self];
+                let mut exprs = self.exprs();
+                for &index in &indices[..indices.len() - 1] {
+                    let ExprVariant::SExpr(scope) = &exprs[index].base.unwrap_derefs().variant
+                    else {
+                        unreachable!("unresolved references are only nested in sexprs")
                     };
 
-                    if let Some(resolved) = resolved {
-                        *reference.cell.borrow_mut() = resolved;
+                    scopes.push(scope);
+                    exprs = scope.exprs();
+                }
+
+                let symbol = &unresolved_at(self.exprs(), &indices).symbol;
+                ScopeError::UnresolvedReference(symbol.span.clone(), suggest(&symbol.name, &scopes))
+            })
+            .collect::<Vec<_>>();
+
+        errors.sort_by_key(|err| match err {
+            ScopeError::UnresolvedReference(span, _) => span.start,
+            _ => unreachable!(),
+        });
+
+        for err in errors {
+            emit(err);
+        }
+    }
+}
+
+impl Unresolved {
+    fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Adds a reference found within the expression at `index` of the
+    /// scope that owns the map.
+    fn push(&mut self, name: &str, skip: usize, (index, occurrence): (usize, Occurrence)) {
+        let depth = self.trail.len();
+        let pending = self.symbols.get_or_insert_with(name, Vec::new);
+        match pending.last_mut() {
+            Some(last) if last.skip == skip && last.depth == depth => {
+                last.occurrences.push((index, occurrence))
+            }
+            _ => pending.push(Pending {
+                skip,
+                depth,
+                occurrences: vec![(index, occurrence)],
+            }),
+        }
+    }
+
+    /// Moves the references of a nested scope, the expression at
+    /// `index`, into this map.
+    fn merge(&mut self, index: usize, nested: Unresolved) {
+        for (name, pending) in nested.symbols {
+            for Pending {
+                skip,
+                depth,
+                mut occurrences,
+            } in pending
+            {
+                for &index in &nested.trail[depth..] {
+                    occurrences = vec![(index, Occurrence::SExpr(occurrences))];
+                }
+
+                self.push(&name, skip, (index, Occurrence::SExpr(occurrences)));
+            }
+        }
+    }
+
+    /// Takes the references to the labels of a scope, along with the
+    /// index of the expression they refer to. References skipping a
+    /// label skip one less, since they're passed on to the parent.
+    ///
+    /// The references are sorted from the outermost to the innermost
+    /// point of the trail.
+    fn take_labelled(
+        &mut self,
+        expr_from_label: &HashMap<Name, (usize, usize)>,
+    ) -> Vec<(Pending, usize)> {
+        let names = if expr_from_label.len() < self.symbols.len() {
+            expr_from_label
+                .keys()
+                .filter(|name| self.symbols.contains_key(name.as_str()))
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            // The references keep the names of their symbols interned
+            self.symbols
+                .keys()
+                .filter_map(|name| Name::get(&name))
+                .filter(|name| expr_from_label.contains_key(name))
+                .collect()
+        };
+
+        let mut labelled = Vec::new();
+        for name in names {
+            let (index, _) = expr_from_label[&name];
+            let mut skipping = Vec::new();
+            for mut pending in self.symbols.remove(name.as_str()).unwrap_or_default() {
+                match pending.skip {
+                    0 => labelled.push((pending, index)),
+                    _ => {
+                        pending.skip -= 1;
+                        skipping.push(pending);
                     }
                 }
-                ExprVariant::SExpr(scope) => {
-                    for (symbol, mut references) in
-                        scope.unresolved_map.take().into_iter().flatten()
-                    {
-                        if let Some((other_index, _)) = expr_from_label.get(&symbol).copied() {
-                            let expr = &exprs[other_index];
-                            for (scope, reference) in references {
-                                // NOTE: Could use paths instead of pointers to avoid unsafe, but
-                                // would be more complicated and less efficient
-                                unsafe {
-                                    let ReferenceVariant::Unresolved(unresolved) = &*reference
-                                    else {
-                                        unreachable!()
-                                    };
-
-                                    match expr.base.resolve_path(&unresolved.path) {
-                                        Ok(path) => {
-                                            *reference =
-                                                ReferenceVariant::Resolved(ResolvedReference {
-                                                    scope,
-                                                    offset: other_index as isize - index as isize,
-                                                    path,
-                                                });
-                                        }
-                                        Err(path) => emit(ScopeError::InvalidPath(path_span(path))),
-                                    }
-                                }
-                            }
-                        } else {
-                            for unresolved in references.iter_mut() {
-                                unresolved.0 += 1;
-                            }
+            }
 
-                            match unresolved_map.entry(symbol) {
-                                hash_map::Entry::Occupied(mut entry) => {
-                                    entry.get_mut().extend(references);
-                                }
-                                hash_map::Entry::Vacant(entry) => {
-                                    entry.insert(references);
-                                }
-                            };
-                        }
+            if !skipping.is_empty() {
+                self.symbols.insert(name.as_str(), skipping);
+            }
+        }
+
+        labelled.sort_by_key(|(pending, _)| usize::MAX - pending.depth);
+        labelled
+    }
+
+    /// The path of expression indices to each reference, from outermost
+    /// to innermost.
+    fn locations(&self) -> Vec<Vec<usize>> {
+        fn locations_rec(
+            occurrences: &Occurrences,
+            indices: &mut Vec<usize>,
+            locations: &mut Vec<Vec<usize>>,
+        ) {
+            for (index, occurrence) in occurrences {
+                indices.push(*index);
+                match occurrence {
+                    Occurrence::Reference => locations.push(indices.clone()),
+                    Occurrence::SExpr(occurrences) => {
+                        locations_rec(occurrences, indices, locations)
                     }
                 }
+
+                indices.pop();
             }
         }
 
-        Self {
-            exprs: exprs.into_boxed_slice(),
-            expr_from_label,
-            unresolved_map: Cell::new(Some(unresolved_map)),
+        let mut locations = Vec::new();
+        for pending in self.symbols.values().flatten() {
+            let mut indices = self.trail[pending.depth..].iter().rev().copied().collect();
+
+            locations_rec(&pending.occurrences, &mut indices, &mut locations);
         }
+
+        locations
     }
 }
 
-impl fmt::Debug for Scope {
-    #[cfg_attr(coverage_nightly, coverage(off))]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        self.exprs.fmt(f)
+/// The expressions of the s-expression at `index`.
+fn nested_exprs(exprs: &[Expr], index: usize) -> &[Expr] {
+    match &exprs[index].base.unwrap_derefs().variant {
+        ExprVariant::SExpr(scope) => scope.exprs(),
+        _ => unreachable!("trail only leads through sexprs"),
+    }
+}
+
+fn nested_exprs_mut(exprs: &mut [Expr], index: usize) -> &mut [Expr] {
+    match &mut exprs[index].base.unwrap_derefs_mut().variant {
+        ExprVariant::SExpr(scope) => &mut Arc::make_mut(&mut scope.inner).exprs,
+        _ => unreachable!("trail only leads through sexprs"),
+    }
+}
+
+/// Resolves references taken by [Unresolved::take_labelled], following
+/// the trail once.
+///
+/// Targets are expressions of `targets`, which is either the scope
+/// itself, or an outer scope that it's placed in at the given index.
+fn resolve_pending(
+    mut exprs: &[Expr],
+    trail: &[usize],
+    pending: &[(Pending, usize)],
+    (targets, outer): (&[Expr], Option<usize>),
+    resolved: &mut Vec<Option<ResolvedReference>>,
+    emit: &mut dyn FnMut(ScopeError),
+) {
+    let mut depth = trail.len();
+    for (pending, target) in pending {
+        while depth > pending.depth {
+            depth -= 1;
+            exprs = nested_exprs(exprs, trail[depth]);
+        }
+
+        let scope = trail.len() - depth + usize::from(outer.is_some());
+        for occurrence in &pending.occurrences {
+            let index = match (outer, trail.last()) {
+                (Some(index), _) => index,
+                (None, Some(&index)) if depth < trail.len() => index,
+                (None, _) => occurrence.0,
+            };
+
+            resolve_unresolved(
+                exprs,
+                std::slice::from_ref(occurrence),
+                &targets[*target].base,
+                (scope, *target as isize - index as isize),
+                resolved,
+                emit,
+            );
+        }
+    }
+}
+
+/// Replaces the references with the results of [resolve_pending],
+/// which visits them in the same order.
+fn patch_pending(
+    mut exprs: &mut [Expr],
+    trail: &[usize],
+    pending: &[(Pending, usize)],
+    resolved: &mut impl Iterator<Item = Option<ResolvedReference>>,
+) {
+    let mut depth = trail.len();
+    for (pending, _) in pending {
+        while depth > pending.depth {
+            depth -= 1;
+            exprs = nested_exprs_mut(exprs, trail[depth]);
+        }
+
+        patch_unresolved(exprs, &pending.occurrences, resolved);
+    }
+}
+
+/// Resolves references to the symbol labelling `target`, where `scope`
+/// and `offset` point to `target` from the outermost expression.
+fn resolve_unresolved(
+    exprs: &[Expr],
+    unresolved: &[(usize, Occurrence)],
+    target: &BaseExpr,
+    (scope, offset): (usize, isize),
+    resolved: &mut Vec<Option<ResolvedReference>>,
+    emit: &mut dyn FnMut(ScopeError),
+) {
+    for (index, occurrence) in unresolved {
+        match (occurrence, &exprs[*index].base.unwrap_derefs().variant) {
+            (Occurrence::Reference, ExprVariant::Reference(reference))
+            | (Occurrence::Reference, ExprVariant::ValueRef(reference)) => {
+                let ReferenceVariant::Unresolved(unresolved) = &reference.variant else {
+                    unreachable!("reference is already resolved")
+                };
+
+                match target.resolve_path(&unresolved.path) {
+                    Ok(path) => resolved.push(Some(ResolvedReference {
+                        scope,
+                        offset,
+                        path,
+                    })),
+                    Err(path) => {
+                        emit(ScopeError::InvalidPath(path_span(path)));
+                        resolved.push(None);
+                    }
+                }
+            }
+            (Occurrence::SExpr(unresolved), ExprVariant::SExpr(child)) => resolve_unresolved(
+                child.exprs(),
+                unresolved,
+                target,
+                (scope + 1, offset),
+                resolved,
+                emit,
+            ),
+            _ => unreachable!("occurrence doesn't match expression"),
+        }
+    }
+}
+
+/// Replaces the references with the results of [resolve_unresolved],
+/// which visits them in the same order.
+fn patch_unresolved(
+    exprs: &mut [Expr],
+    unresolved: &[(usize, Occurrence)],
+    resolved: &mut impl Iterator<Item = Option<ResolvedReference>>,
+) {
+    for (index, occurrence) in unresolved {
+        match (
+            occurrence,
+            &mut exprs[*index].base.unwrap_derefs_mut().variant,
+        ) {
+            (Occurrence::Reference, ExprVariant::Reference(reference))
+            | (Occurrence::Reference, ExprVariant::ValueRef(reference)) => {
+                if let Some(resolved) = resolved.next().expect("resolved every reference") {
+                    reference.variant = ReferenceVariant::Resolved(resolved);
+                }
+            }
+            (Occurrence::SExpr(unresolved), ExprVariant::SExpr(child)) => {
+                let child = Arc::make_mut(&mut child.inner);
+                patch_unresolved(&mut child.exprs, unresolved, resolved)
+            }
+            _ => unreachable!("occurrence doesn't match expression"),
+        }
+    }
+}
+
+/// Moves references that point outside of the scope being
+/// [nested](Scope::nest) out by another scope, where `depth` is the
+/// number of scopes `exprs` is nested within it.
+fn shift_outer_references(exprs: &mut [Expr], depth: usize) {
+    for expr in exprs {
+        match &mut expr.base.unwrap_derefs_mut().variant {
+            ExprVariant::Reference(reference) | ExprVariant::ValueRef(reference) => {
+                if let ReferenceVariant::Resolved(resolved) = &mut reference.variant {
+                    if resolved.scope > depth {
+                        resolved.scope += 1;
+                    }
+                }
+            }
+            ExprVariant::SExpr(scope) => {
+                let scope = Arc::make_mut(&mut scope.inner);
+                shift_outer_references(&mut scope.exprs, depth + 1);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Finds the unresolved reference at the given location.
+fn unresolved_at<'a>(exprs: &'a [Expr], indices: &[usize]) -> &'a UnresolvedReference {
+    let (&index, indices) = indices.split_first().expect("at least one index");
+    match &exprs[index].base.unwrap_derefs().variant {
+        ExprVariant::SExpr(scope) => unresolved_at(scope.exprs(), indices),
+        ExprVariant::Reference(reference) | ExprVariant::ValueRef(reference) => {
+            match &reference.variant {
+                ReferenceVariant::Unresolved(unresolved) => unresolved,
+                ReferenceVariant::Resolved(_) => unreachable!("reference is already resolved"),
+            }
+        }
+        _ => unreachable!("location doesn't point to a reference"),
     }
 }
 
-impl Clone for Scope {
+/// Finds the closest label to a misspelled symbol, preferring labels in
+/// inner scopes.
+fn suggest(name: &Name, scopes: &[&Scope]) -> Option<String> {
+    let name = name.as_str();
+    let max_distance = name.chars().count() / 3;
+    scopes
+        .iter()
+        .rev()
+        .enumerate()
+        .flat_map(|(depth, scope)| {
+            scope
+                .inner
+                .expr_from_label
+                .keys()
+                .map(move |label| (edit_distance(name, label.as_str()), depth, label))
+        })
+        .filter(|(distance, _, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, _, label)| label.into())
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+impl fmt::Debug for Scope {
     #[cfg_attr(coverage_nightly, coverage(off))]
-    fn clone(&self) -> Self {
-        Self::try_from_exprs(self.exprs.iter().cloned()).unwrap()
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        self.inner.exprs.fmt(f)
     }
 }
 
 impl PartialEq for Scope {
     fn eq(&self, other: &Self) -> bool {
-        self.exprs.eq(&other.exprs)
+        self.inner.exprs.eq(&other.inner.exprs)
     }
 }
 
@@ -184,7 +600,10 @@ impl IntoIterator for Scope {
 
     fn into_iter(self) -> Self::IntoIter {
         // into_vec: https://github.com/rust-lang/rust/issues/59878
-        self.exprs.into_vec().into_iter()
+        Arc::unwrap_or_clone(self.inner)
+            .exprs
+            .into_vec()
+            .into_iter()
     }
 }
 
@@ -192,6 +611,9 @@ impl IntoIterator for Scope {
 pub enum ScopeError {
     DuplicateLabel(Range<usize>, Range<usize>),
     InvalidPath(Range<usize>),
+    /// A symbol that isn't labelled in any enclosing scope, along with
+    /// the closest label that is.
+    UnresolvedReference(Range<usize>, Option<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -217,13 +639,26 @@ impl Expr {
         span: Range<usize>,
         natural: impl Into<Natural>,
     ) -> Self {
-        Self::variant(labels, span, ExprVariant::Natural(natural.into()))
+        let literal = NaturalLiteral {
+            value: natural.into(),
+            source: None,
+        };
+
+        Self::variant(labels, span, ExprVariant::Natural(literal))
+    }
+
+    pub fn text(
+        labels: impl Into<Box<Labels>>,
+        span: Range<usize>,
+        text: impl Into<String>,
+    ) -> Self {
+        Self::variant(labels, span, ExprVariant::Text(text.into()))
     }
 
     pub fn unresolved_symbol(
         labels: impl Into<Box<Labels>>,
         span: Range<usize>,
-        name: impl Into<String>,
+        name: impl Into<Name>,
     ) -> Self {
         Self::unresolved_reference(labels, span.clone(), Symbol::new(span, name), [])
     }
@@ -255,6 +690,18 @@ impl Expr {
         )
     }
 
+    /// A reference to a [Builtin] from a scope resolved against the
+    /// [prelude](crate::prelude::prelude), `scope` levels up from
+    /// the outermost expression.
+    pub fn builtin_reference(
+        labels: impl Into<Box<Labels>>,
+        span: Range<usize>,
+        scope: usize,
+        builtin: Builtin,
+    ) -> Self {
+        Self::resolved_reference(labels, span, scope, builtin.offset(), [])
+    }
+
     pub fn sexpr(
         labels: impl Into<Box<Labels>>,
         span: Range<usize>,
@@ -263,6 +710,48 @@ impl Expr {
         Self::variant(labels, span, ExprVariant::SExpr(scope.into()))
     }
 
+    pub fn unresolved_value_ref(
+        labels: impl Into<Box<Labels>>,
+        span: Range<usize>,
+        symbol: Symbol,
+        path: impl Into<Box<UnresolvedPath>>,
+    ) -> Self {
+        Self::variant(
+            labels,
+            span,
+            ExprVariant::ValueRef(Reference::unresolved(symbol, path)),
+        )
+    }
+
+    pub fn resolved_value_ref(
+        labels: impl Into<Box<Labels>>,
+        span: Range<usize>,
+        scope: usize,
+        offset: isize,
+        path: impl Into<Box<ResolvedPath>>,
+    ) -> Self {
+        Self::variant(
+            labels,
+            span,
+            ExprVariant::ValueRef(Reference::resolved(scope, offset, path)),
+        )
+    }
+
+    pub fn deref(labels: impl Into<Box<Labels>>, span: Range<usize>, base: BaseExpr) -> Self {
+        Self::variant(labels, span, ExprVariant::Deref(Box::new(base)))
+    }
+
+    /// Checks if two expressions are the same, ignoring their spans.
+    pub fn eq_ignoring_spans(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(other.labels.iter())
+                .all(|(label, other)| label.name == other.name && label.doc == other.doc)
+            && self.base.eq_ignoring_spans(&other.base)
+    }
+
     pub fn span(&self) -> Range<usize> {
         let start = self
             .labels
@@ -276,19 +765,127 @@ impl Expr {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BaseExpr {
+    /// The file this expression's span is in. [Label]s and [Symbol]s
+    /// don't record a file, so look it up on the expression that
+    /// owns them.
+    pub file: FileId,
+
     pub span: Range<usize>,
     pub variant: ExprVariant,
 }
 
 impl BaseExpr {
     pub(crate) fn variant(span: Range<usize>, variant: ExprVariant) -> Self {
-        Self { span, variant }
+        Self {
+            file: FileId::default(),
+            span,
+            variant,
+        }
+    }
+
+    /// Moves this expression, and every expression within it, to `file`.
+    pub fn set_file(&mut self, file: FileId) {
+        self.file = file;
+        match &mut self.variant {
+            ExprVariant::Deref(inner) => inner.set_file(file),
+            ExprVariant::SExpr(scope) => {
+                for expr in Arc::make_mut(&mut scope.inner).exprs.iter_mut() {
+                    expr.base.set_file(file);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Checks if two expressions are the same, ignoring their spans.
+    pub fn eq_ignoring_spans(&self, other: &Self) -> bool {
+        if self.file != other.file {
+            return false;
+        }
+
+        match (&self.variant, &other.variant) {
+            (ExprVariant::Reference(reference), ExprVariant::Reference(other))
+            | (ExprVariant::ValueRef(reference), ExprVariant::ValueRef(other)) => {
+                reference.eq_ignoring_spans(other)
+            }
+            (ExprVariant::Deref(base), ExprVariant::Deref(other)) => base.eq_ignoring_spans(other),
+            (ExprVariant::SExpr(scope), ExprVariant::SExpr(other)) => {
+                scope.eq_ignoring_spans(other)
+            }
+            (variant, other) => variant == other,
+        }
     }
 
     pub(crate) fn with_labels(self, labels: Box<Labels>) -> Expr {
         Expr { labels, base: self }
     }
 
+    /// Checks if this is an unresolved reference to `name` without a path.
+    pub(crate) fn is_symbol(&self, name: &str) -> bool {
+        match &self.variant {
+            ExprVariant::Reference(reference) => match &reference.variant {
+                ReferenceVariant::Unresolved(unresolved) => {
+                    unresolved.symbol.name == name
+                        && unresolved.skip == 0
+                        && unresolved.path.is_empty()
+                }
+                ReferenceVariant::Resolved(_) => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Skips past any dereferences to the expression being dereferenced.
+    fn unwrap_derefs(&self) -> &Self {
+        let mut base = self;
+        while let ExprVariant::Deref(inner) = &base.variant {
+            base = inner;
+        }
+
+        base
+    }
+
+    fn unwrap_derefs_mut(&mut self) -> &mut Self {
+        match self.variant {
+            ExprVariant::Deref(ref mut inner) => inner.unwrap_derefs_mut(),
+            _ => self,
+        }
+    }
+
+    /// The number of symbols that the scope of an s-expression couldn't
+    /// resolve.
+    fn unresolved_len(&self) -> usize {
+        match &self.unwrap_derefs().variant {
+            ExprVariant::SExpr(scope) => scope.inner.unresolved.symbols.len(),
+            _ => 0,
+        }
+    }
+
+    /// Moves the references within this expression that its scopes
+    /// couldn't resolve into `unresolved`, where this is the expression
+    /// at `index`.
+    fn take_unresolved(&mut self, index: usize, unresolved: &mut Unresolved) {
+        match &mut self.unwrap_derefs_mut().variant {
+            ExprVariant::Reference(reference) | ExprVariant::ValueRef(reference) => {
+                if let ReferenceVariant::Unresolved(reference) = &reference.variant {
+                    unresolved.push(
+                        reference.symbol.name.as_str(),
+                        reference.skip,
+                        (index, Occurrence::Reference),
+                    );
+                }
+            }
+            // Scopes can be shared, so only copy them if there's
+            // something to take
+            ExprVariant::SExpr(scope) if scope.inner.unresolved.is_empty() => (),
+            ExprVariant::SExpr(scope) => {
+                let nested = mem::take(&mut Arc::make_mut(&mut scope.inner).unresolved);
+                unresolved.merge(index, nested);
+            }
+            _ => (),
+        }
+    }
+
     fn resolve_path<'p>(
         &self,
         mut unresolved: &'p UnresolvedPath,
@@ -300,11 +897,11 @@ impl BaseExpr {
                 return Err(unresolved);
             };
 
-            let Some((index, _)) = scope.expr_from_label.get(&label.name).copied() else {
+            let Some((index, _)) = scope.inner.expr_from_label.get(&label.name).copied() else {
                 return Err(unresolved);
             };
 
-            expr = &scope.exprs[index].base;
+            expr = &scope.inner.exprs[index].base;
             unresolved = remainder;
             resolved.push(index);
         }
@@ -326,20 +923,25 @@ impl BaseExpr {
     ) -> Result<Self, (Box<UnresolvedPath>, usize)> {
         Ok(match path.get(depth) {
             Some(label) => Self {
+                file: self.file,
                 span: self.span.start..path.last().unwrap().span.end,
                 variant: match self.variant {
-                    ExprVariant::Natural(_) => return Err((path, depth)),
+                    ExprVariant::Natural(_)
+                    | ExprVariant::Text(_)
+                    | ExprVariant::Builtin(_)
+                    | ExprVariant::ValueRef(_)
+                    | ExprVariant::Deref(_) => return Err((path, depth)),
                     ExprVariant::Reference(reference) => ExprVariant::Reference(Reference {
-                        cell: RefCell::new(match reference.cell.into_inner() {
+                        variant: match reference.variant {
                             ReferenceVariant::Unresolved(unresolved) => {
                                 // into_vec: https://github.com/rust-lang/rust/issues/59878
                                 ReferenceVariant::Unresolved(unresolved.join(path.into_vec()))
                             }
                             ReferenceVariant::Resolved(_) => unreachable!(),
-                        }),
+                        },
                     }),
                     ExprVariant::SExpr(scope) => {
-                        match scope.expr_from_label.get(&label.name).copied() {
+                        match scope.inner.expr_from_label.get(&label.name).copied() {
                             Some((index, _)) => {
                                 scope
                                     .into_iter()
@@ -361,33 +963,90 @@ impl BaseExpr {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExprVariant {
-    Natural(Natural),
+    Natural(NaturalLiteral),
+    Text(String),
     Reference(Reference),
+    ValueRef(Reference),
+    Deref(Box<BaseExpr>),
     SExpr(Scope),
+    Builtin(Builtin),
+}
+
+/// A natural, along with the way it was written.
+#[derive(Debug, Clone)]
+pub struct NaturalLiteral {
+    pub value: Natural,
+
+    /// The source code of the literal, like `0xFF_FF` or `2^16`, or
+    /// [None] if it wasn't parsed.
+    pub source: Option<Box<str>>,
 }
 
+/// Literals are compared by value, no matter how they were written.
+impl PartialEq for NaturalLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for NaturalLiteral {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Reference {
-    cell: RefCell<ReferenceVariant>,
+    variant: ReferenceVariant,
 }
 
 impl Reference {
     pub fn unresolved(symbol: Symbol, path: impl Into<Box<UnresolvedPath>>) -> Self {
+        Self::unresolved_skipping(symbol, path, 0)
+    }
+
+    /// An unresolved reference that skips the `skip` innermost labels
+    /// matching `symbol`.
+    pub fn unresolved_skipping(
+        symbol: Symbol,
+        path: impl Into<Box<UnresolvedPath>>,
+        skip: usize,
+    ) -> Self {
         Self {
-            cell: RefCell::new(ReferenceVariant::Unresolved(UnresolvedReference {
+            variant: ReferenceVariant::Unresolved(UnresolvedReference {
                 symbol,
                 path: path.into(),
-            })),
+                skip,
+            }),
         }
     }
 
     pub fn resolved(scope: usize, offset: isize, path: impl Into<Box<[usize]>>) -> Self {
         Self {
-            cell: RefCell::new(ReferenceVariant::Resolved(ResolvedReference {
+            variant: ReferenceVariant::Resolved(ResolvedReference {
                 scope,
                 offset,
                 path: path.into(),
-            })),
+            }),
+        }
+    }
+
+    pub fn variant(&self) -> &ReferenceVariant {
+        &self.variant
+    }
+
+    /// Checks if two references are the same, ignoring the spans of
+    /// their symbols.
+    pub fn eq_ignoring_spans(&self, other: &Self) -> bool {
+        match (&self.variant, &other.variant) {
+            (ReferenceVariant::Unresolved(reference), ReferenceVariant::Unresolved(other)) => {
+                reference.skip == other.skip
+                    && reference.symbol.name == other.symbol.name
+                    && reference.symbol.doc == other.symbol.doc
+                    && reference.path.len() == other.path.len()
+                    && reference
+                        .path
+                        .iter()
+                        .zip(other.path.iter())
+                        .all(|(label, other)| label.name == other.name && label.doc == other.doc)
+            }
+            (reference, other) => reference == other,
         }
     }
 }
@@ -403,6 +1062,10 @@ pub enum ReferenceVariant {
 pub struct UnresolvedReference {
     pub symbol: Symbol,
     pub path: Box<UnresolvedPath>,
+
+    /// The number of labels matching `symbol` to skip, so labels
+    /// shadowed by inner scopes can still be referred to.
+    pub skip: usize,
 }
 
 impl UnresolvedReference {
@@ -411,6 +1074,7 @@ impl UnresolvedReference {
         Self {
             symbol: self.symbol,
             path: self.path.into_vec().into_iter().chain(other).collect(),
+            skip: self.skip,
         }
     }
 }
@@ -438,29 +1102,43 @@ pub type Labels = [Label];
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Label {
     pub span: Range<usize>,
-    pub name: String,
+    pub name: Name,
+    pub doc: Option<String>,
 }
 
 impl Label {
-    pub fn new(span: Range<usize>, name: impl Into<String>) -> Self {
+    pub fn new(span: Range<usize>, name: impl Into<Name>) -> Self {
         Self {
             span,
             name: name.into(),
+            doc: None,
         }
     }
+
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Symbol {
     pub span: Range<usize>,
-    pub name: String,
+    pub name: Name,
+    pub doc: Option<String>,
 }
 
 impl Symbol {
-    pub fn new(span: Range<usize>, name: impl Into<String>) -> Self {
+    pub fn new(span: Range<usize>, name: impl Into<Name>) -> Self {
         Self {
             span,
             name: name.into(),
+            doc: None,
         }
     }
+
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
 }
@@ -0,0 +1,320 @@
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read, Write},
+    process::ExitCode,
+};
+
+use chumsky::Parser;
+
+use ari::{
+    ast::{BaseExpr, Expr, ExprVariant, Reference, ReferenceVariant},
+    cst::Cst,
+    diagnostic::{Renderer, Style},
+    eval,
+    format::{self, Config},
+    intern::Name,
+    module::Loader,
+    parser::open_parser,
+    source::{FileId, SourceMap},
+};
+
+const USAGE: &str = "\
+usage: ari <command> [args]
+
+commands:
+  check [FILE]             Parse and resolve a program, reporting any errors
+  ast [FILE]               Print the resolved syntax tree of a program
+  eval [FILE] LABEL        Print the type labelled LABEL and its number of states
+  fmt [--check] [FILE]...  Format files in place, or stdin to stdout
+
+Programs are read from stdin when no file is given. With --check, fmt
+lists the files that aren't formatted instead, failing if there are any.
+
+ast prints one expression per line, indented by two spaces for each
+s-expression it's in, as its span, its labels, and what it is:
+
+  natural VALUE                A natural, in decimal or as a power of two
+  text \"TEXT\"                  Text, with its quotes doubled up
+  reference SCOPE OFFSET PATH  The expression OFFSET away from this one,
+                               SCOPE scopes out, then the index of each
+                               expression along its PATH, if any
+  value-ref SCOPE OFFSET PATH  A value reference, like reference
+  unresolved SYMBOL:PATH       A reference that couldn't be resolved
+  builtin NAME                 A builtin of the prelude
+  deref                        A dereference, followed by what it
+                               dereferences on the same line
+  sexpr                        An s-expression, followed by its
+                               expressions
+";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["check", path @ ..] if path.len() <= 1 => check(path.first().copied()),
+        ["ast", path @ ..] if path.len() <= 1 => ast(path.first().copied()),
+        ["eval", label] => eval(None, label),
+        ["eval", path, label] => eval(Some(path), label),
+        ["fmt", args @ ..] => fmt(args),
+        ["help" | "-h" | "--help", ..] => {
+            exit_code(failed_to_write(write!(io::stdout().lock(), "{USAGE}")))
+        }
+        _ => usage_error(),
+    }
+}
+
+fn usage_error() -> ExitCode {
+    eprint!("{USAGE}");
+    ExitCode::from(2)
+}
+
+fn exit_code(failed: bool) -> ExitCode {
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+/// Checks if writing to stdout failed, reporting why. Stdout closing
+/// early (like in `ari ast | head`) only stops the output.
+fn failed_to_write(result: io::Result<()>) -> bool {
+    match result {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("error: couldn't write to stdout: {err}");
+            true
+        }
+        _ => false,
+    }
+}
+
+fn style() -> Style {
+    match io::stderr().is_terminal() {
+        true => Style::Colored,
+        false => Style::Plain,
+    }
+}
+
+fn read_stdin() -> Result<String, ExitCode> {
+    let mut source = String::new();
+    match io::stdin().read_to_string(&mut source) {
+        Ok(_) => Ok(source),
+        Err(err) => {
+            eprintln!("error: couldn't read stdin: {err}");
+            Err(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// Loads a program along with everything it imports, from stdin if
+/// there's no path.
+fn load(path: Option<&str>) -> Result<(Loader, FileId), ExitCode> {
+    let mut loader = Loader::new();
+    let id = match path {
+        Some(path) => loader.load(path).map_err(|err| {
+            eprintln!("error: couldn't read {path}: {err}");
+            ExitCode::FAILURE
+        })?,
+        None => loader.load_source("<stdin>", read_stdin()?),
+    };
+
+    Ok((loader, id))
+}
+
+/// Reports the errors in every loaded file, returning whether there
+/// were any.
+fn report(loader: &Loader) -> bool {
+    let renderer = Renderer::new(loader.sources()).with_style(style());
+    let mut failed = false;
+    for err in loader.errors() {
+        eprintln!("{}", renderer.render(err));
+        failed = true;
+    }
+
+    failed
+}
+
+fn check(path: Option<&str>) -> ExitCode {
+    match load(path) {
+        Ok((loader, _)) => exit_code(report(&loader)),
+        Err(code) => code,
+    }
+}
+
+fn ast(path: Option<&str>) -> ExitCode {
+    let (loader, id) = match load(path) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+
+    let mut failed = report(&loader);
+    if let Some(scope) = &loader.file(id).scope {
+        failed |= failed_to_write(write_exprs(&mut io::stdout().lock(), scope.exprs(), 0));
+    }
+
+    exit_code(failed)
+}
+
+/// Writes expressions in the format described in [USAGE], indented by
+/// `depth` levels.
+fn write_exprs(out: &mut impl Write, exprs: &[Expr], depth: usize) -> io::Result<()> {
+    for expr in exprs {
+        write!(
+            out,
+            "{:indent$}{}..{}",
+            "",
+            expr.span().start,
+            expr.span().end,
+            indent = depth * 2
+        )?;
+        for label in expr.labels.iter() {
+            write!(out, " :{}", label.name)?;
+        }
+
+        write_base(out, &expr.base, depth)?;
+    }
+
+    Ok(())
+}
+
+fn write_base(out: &mut impl Write, base: &BaseExpr, depth: usize) -> io::Result<()> {
+    match &base.variant {
+        ExprVariant::Natural(natural) => writeln!(out, " natural {}", natural.value),
+        ExprVariant::Text(text) => writeln!(out, " text \"{}\"", text.replace('"', "\"\"")),
+        ExprVariant::Reference(reference) => write_reference(out, "reference", reference),
+        ExprVariant::ValueRef(reference) => write_reference(out, "value-ref", reference),
+        ExprVariant::Deref(inner) => {
+            write!(out, " deref")?;
+            write_base(out, inner, depth)
+        }
+        ExprVariant::SExpr(scope) => {
+            writeln!(out, " sexpr")?;
+            write_exprs(out, scope.exprs(), depth + 1)
+        }
+        ExprVariant::Builtin(builtin) => writeln!(out, " builtin {}", builtin.names()[0]),
+    }
+}
+
+fn write_reference(out: &mut impl Write, kind: &str, reference: &Reference) -> io::Result<()> {
+    match reference.variant() {
+        ReferenceVariant::Resolved(resolved) => {
+            write!(out, " {kind} {} {}", resolved.scope, resolved.offset)?;
+            for index in resolved.path.iter() {
+                write!(out, " {index}")?;
+            }
+        }
+        ReferenceVariant::Unresolved(unresolved) => {
+            write!(out, " unresolved {}", unresolved.symbol.name)?;
+            for label in unresolved.path.iter() {
+                write!(out, ":{}", label.name)?;
+            }
+        }
+    }
+
+    writeln!(out)
+}
+
+fn eval(path: Option<&str>, label: &str) -> ExitCode {
+    let (loader, id) = match load(path) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+
+    if report(&loader) {
+        return ExitCode::FAILURE;
+    }
+
+    let scope = (loader.file(id).scope.as_ref()).expect("files without errors have a scope");
+    let Some(index) = Name::get(label).and_then(|name| scope.index_of(&name)) else {
+        eprintln!("error: nothing is labelled `{label}`");
+        return ExitCode::FAILURE;
+    };
+
+    match eval::eval_at(scope, index) {
+        Ok(ty) => {
+            let states = match &ty.states {
+                Some(states) => states.to_string(),
+                None if ty.is_recursive() => "unbounded".to_owned(),
+                None => "depends on a runtime value".to_owned(),
+            };
+
+            exit_code(failed_to_write(writeln!(
+                io::stdout().lock(),
+                "type: {ty}\nstates: {states}"
+            )))
+        }
+        Err(err) => {
+            let renderer = Renderer::new(loader.sources()).with_style(style());
+            eprintln!("{}", renderer.render_eval(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn fmt(args: &[&str]) -> ExitCode {
+    let mut check = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match *arg {
+            "--check" => check = true,
+            option if option.starts_with('-') => {
+                eprintln!("error: unknown option `{option}`");
+                return usage_error();
+            }
+            path => paths.push(path),
+        }
+    }
+
+    let mut sources = SourceMap::new();
+    let mut failed = false;
+    if paths.is_empty() {
+        match read_stdin() {
+            Ok(source) => _ = sources.add("<stdin>", source),
+            Err(code) => return code,
+        }
+    }
+
+    for path in &paths {
+        match fs::read_to_string(path) {
+            Ok(source) => _ = sources.add(*path, source),
+            Err(err) => {
+                eprintln!("error: couldn't read {path}: {err}");
+                failed = true;
+            }
+        }
+    }
+
+    let renderer = Renderer::new(&sources).with_style(style());
+    let mut stdout = io::stdout().lock();
+    for (id, file) in sources.files() {
+        let cst = Cst::parse(&file.source);
+
+        // References can't be resolved without the files they import, so
+        // they're left open, but any other error leaves the source in doubt
+        let (_, errors) = open_parser().parse_recovery(file.source.as_str());
+        if !errors.is_empty() {
+            for err in errors {
+                eprintln!("{}", renderer.render(&err.in_file(id)));
+            }
+
+            failed = true;
+            continue;
+        }
+
+        let formatted = format::source(&cst).pretty(&Config::default());
+        if check {
+            if formatted != file.source {
+                failed = true;
+                failed_to_write(writeln!(stdout, "{}", file.path.display()));
+            }
+        } else if paths.is_empty() {
+            failed |= failed_to_write(write!(stdout, "{formatted}"));
+        } else if formatted != file.source {
+            if let Err(err) = fs::write(&file.path, formatted) {
+                eprintln!("error: couldn't write {}: {err}", file.path.display());
+                failed = true;
+            }
+        }
+    }
+
+    exit_code(failed)
+}
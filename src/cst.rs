@@ -0,0 +1,199 @@
+use std::{fmt, ops::Range};
+
+use chumsky::Parser;
+
+use crate::{
+    ast::{Expr, Scope},
+    parser::{parser, tokens, Error},
+};
+
+/// A lossless concrete syntax tree, which keeps every character of its
+/// source, including whitespace and comments.
+///
+/// Writing the tree out with [Display](fmt::Display) reproduces the
+/// source exactly, and its spans are the same as the spans of the
+/// [Scope] parsed from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cst {
+    tokens: Vec<Token>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Whitespace,
+    /// A comment from `;` to the end of the line.
+    LineComment,
+    /// A comment wrapped in `#|` and `|#`, which can be nested.
+    BlockComment,
+    LeftParen,
+    RightParen,
+    Colon,
+    At,
+    Dollar,
+    Natural,
+    /// A symbol, including any leading `\`.
+    Symbol,
+    /// A symbol wrapped in `'`.
+    QuotedSymbol,
+    Text,
+}
+
+/// A node of a [Cst], referring to its tokens by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Token(usize),
+
+    /// Parenthesized nodes, which are unclosed if the source ends
+    /// before the `)`.
+    List {
+        open: usize,
+        children: Vec<Node>,
+        close: Option<usize>,
+    },
+}
+
+impl Cst {
+    pub fn parse(source: &str) -> Self {
+        // Errors are reported when parsing the scope instead
+        let (tokens, _) = tokens().parse_recovery(source);
+        let mut chars = source.chars();
+        let tokens = tokens
+            .expect("every character is part of a token")
+            .into_iter()
+            .map(|(kind, span)| Token {
+                kind,
+                text: chars.by_ref().take(span.len()).collect(),
+                span,
+            })
+            .collect::<Vec<_>>();
+
+        // Lists that are still open, along with their children so far
+        let mut lists = Vec::<(usize, Vec<Node>)>::new();
+        let mut nodes = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            let node = match token.kind {
+                TokenKind::LeftParen => {
+                    lists.push((index, Vec::new()));
+                    continue;
+                }
+                TokenKind::RightParen => match lists.pop() {
+                    Some((open, children)) => Node::List {
+                        open,
+                        children,
+                        close: Some(index),
+                    },
+                    None => Node::Token(index),
+                },
+                _ => Node::Token(index),
+            };
+
+            match lists.last_mut() {
+                Some((_, children)) => children.push(node),
+                None => nodes.push(node),
+            }
+        }
+
+        while let Some((open, children)) = lists.pop() {
+            let node = Node::List {
+                open,
+                children,
+                close: None,
+            };
+
+            match lists.last_mut() {
+                Some((_, children)) => children.push(node),
+                None => nodes.push(node),
+            }
+        }
+
+        Self { tokens, nodes }
+    }
+
+    /// Every token, in source order.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The top-level nodes, where an unmatched `)` is a token.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Parses the abstract syntax tree of the source.
+    pub fn scope(&self) -> (Option<Scope>, Vec<Error>) {
+        parser().parse_recovery(self.to_string().as_str())
+    }
+
+    /// The comments leading up to an expression of the [Scope], in
+    /// source order.
+    ///
+    /// A comment on the same line as the end of the previous expression
+    /// belongs to that expression instead, like `:a 1 ; one`, but one
+    /// after a `(` doesn't, like `(; one`.
+    pub fn comments(&self, expr: &Expr) -> Vec<&Token> {
+        let start = expr.span().start;
+        let index = self
+            .tokens
+            .partition_point(|token| token.span.start < start);
+
+        let mut comments = Vec::new();
+        let mut newline = false;
+        for token in self.tokens[..index].iter().rev() {
+            match token.kind {
+                TokenKind::Whitespace => newline = token.text.contains('\n'),
+                TokenKind::LineComment | TokenKind::BlockComment => {
+                    comments.push(token);
+                    newline = false;
+                }
+                kind => {
+                    if !newline && kind.ends_expr() {
+                        comments.pop();
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        comments.reverse();
+        comments
+    }
+}
+
+impl fmt::Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tokens
+            .iter()
+            .try_for_each(|token| f.write_str(&token.text))
+    }
+}
+
+impl TokenKind {
+    /// Checks if this is whitespace or a comment.
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+
+    /// Checks if an expression can end with this token.
+    pub fn ends_expr(self) -> bool {
+        matches!(
+            self,
+            TokenKind::RightParen
+                | TokenKind::Natural
+                | TokenKind::Symbol
+                | TokenKind::QuotedSymbol
+                | TokenKind::Text
+        )
+    }
+}
@@ -0,0 +1,451 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::{
+    ast::{BaseExpr, Expr, ExprVariant, ReferenceVariant, ResolvedReference, Scope},
+    prelude::{prelude, Builtin},
+    source::FileId,
+};
+
+/// A cycle of references between expressions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The file the references are in, since imported files can't
+    /// reference back into the files importing them.
+    pub file: FileId,
+
+    /// The spans of the references forming the cycle, in source order.
+    pub references: Box<[Range<usize>]>,
+
+    /// Whether the cycle passes through a dereference, an optional `?`,
+    /// or a sum with an alternative that isn't recursive, like the `1` in
+    /// `:list (+ 1 (* byte list))`.
+    ///
+    /// Unguarded cycles like `:a b :b a` only alias themselves, while
+    /// `:a (* 256 a)` and `:a (+ 0 a)` never bottom out, so neither
+    /// describes a type. A label that directly references itself (`:a a`)
+    /// is the top type `_` instead, and isn't a cycle.
+    pub guarded: bool,
+}
+
+/// Finds every cycle between the expressions of a resolved scope,
+/// ignoring references to outer scopes (like the [prelude]).
+///
+/// An unguarded cycle is reported as is, even if it's part of a larger
+/// guarded cycle.
+pub fn cycles(scope: &Scope) -> Box<[Cycle]> {
+    let graph = Graph::new(scope);
+    let mut cycles = graph
+        .cyclic_components()
+        .into_iter()
+        .map(|(component, guarded)| graph.cycle(&component, guarded))
+        .collect::<Vec<_>>();
+
+    cycles.sort_by_key(|cycle| cycle.references.first().map(|span| span.start));
+    cycles.into_boxed_slice()
+}
+
+/// Finds the expressions of a resolved scope that are only part of
+/// [guarded](Cycle::guarded) cycles, by their position as the index of
+/// the expression in each enclosing scope.
+pub(crate) fn guarded_positions(scope: &Scope) -> HashSet<Box<[usize]>> {
+    let graph = Graph::new(scope);
+    graph
+        .cyclic_components()
+        .into_iter()
+        .filter(|(_, guarded)| *guarded)
+        .flat_map(|(component, _)| component)
+        .map(|node| graph.positions[node].clone())
+        .collect()
+}
+
+/// Dependencies between expressions, where each node is an expression.
+struct Graph {
+    /// The index of each node's expression in each enclosing scope.
+    positions: Vec<Box<[usize]>>,
+
+    /// The file and span of each node that's a reference.
+    references: Vec<Option<(FileId, Range<usize>)>>,
+
+    /// Whether each node is a type without any values, which can't end
+    /// a recursion.
+    empty: Vec<bool>,
+
+    /// The node of the first expression of each node that's an
+    /// s-expression.
+    nested: Vec<Option<usize>>,
+
+    /// The nodes each node depends on, and what could guard the
+    /// dependency.
+    edges: Vec<Vec<(usize, Guard)>>,
+}
+
+/// A scope enclosing the expressions being added to a [Graph].
+#[derive(Clone, Copy)]
+struct Frame<'a> {
+    scope: &'a Scope,
+
+    /// The index of the expression being added.
+    index: usize,
+
+    /// The node of the first expression of the scope, unless it's
+    /// outside of the graph (like the prelude).
+    first: Option<usize>,
+}
+
+/// What a dependency passes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Guard {
+    None,
+
+    /// A dereference, or the argument of an optional `?`, which can
+    /// always be left out.
+    Always,
+
+    /// An argument of a sum, which only guards a cycle if the sum has
+    /// another alternative outside of it.
+    Alternative,
+}
+
+impl Graph {
+    fn new(scope: &Scope) -> Self {
+        let mut graph = Self {
+            positions: Vec::new(),
+            references: Vec::new(),
+            empty: Vec::new(),
+            nested: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        // Nodes are numbered by position, since a shared scope can be
+        // at more than one
+        let first = graph.add_nodes(scope, &mut Vec::new());
+
+        // Resolve heads against the prelude to tell sums apart
+        let prelude = prelude();
+        let outer = Frame {
+            scope: &prelude,
+            index: prelude.exprs().len(),
+            first: None,
+        };
+
+        let root = Frame {
+            scope,
+            index: 0,
+            first: Some(first),
+        };
+
+        graph.add_edges(&mut vec![outer, root]);
+        graph
+    }
+
+    /// Adds a node for each expression of a scope, numbering them in
+    /// order, and returns the node of the first one.
+    fn add_nodes(&mut self, scope: &Scope, path: &mut Vec<usize>) -> usize {
+        let first = self.edges.len();
+        for (index, expr) in scope.exprs().iter().enumerate() {
+            path.push(index);
+            self.positions.push(path.as_slice().into());
+            path.pop();
+            let (base, _) = unwrap_derefs(expr);
+            self.references.push(match &base.variant {
+                ExprVariant::Reference(_) => Some((base.file, base.span.clone())),
+                _ => None,
+            });
+
+            self.empty.push(false);
+            self.nested.push(None);
+            self.edges.push(Vec::new());
+        }
+
+        for (index, expr) in scope.exprs().iter().enumerate() {
+            let (base, _) = unwrap_derefs(expr);
+            if let ExprVariant::SExpr(scope) = &base.variant {
+                path.push(index);
+                self.nested[first + index] = Some(self.add_nodes(scope, path));
+                path.pop();
+            }
+        }
+
+        first
+    }
+
+    /// Adds the dependencies of the expressions in the scope of the
+    /// innermost frame.
+    fn add_edges(&mut self, frames: &mut Vec<Frame>) {
+        let Frame { scope, first, .. } = *frames.last().unwrap();
+        let first = first.expect("scopes within the graph have nodes");
+        for (index, expr) in scope.exprs().iter().enumerate() {
+            frames.last_mut().unwrap().index = index;
+            let node = first + index;
+            let (base, dereferenced) = unwrap_derefs(expr);
+            match &base.variant {
+                ExprVariant::Reference(reference) => {
+                    let ReferenceVariant::Resolved(reference) = reference.variant() else {
+                        continue;
+                    };
+
+                    let Some((_, Some(target))) = self.find_target(frames, reference) else {
+                        continue;
+                    };
+
+                    // Directly referencing itself is the top type
+                    if target != node || dereferenced {
+                        self.edges[node].push((target, guard(dereferenced, Guard::None)));
+                    }
+                }
+                ExprVariant::Natural(natural) => self.empty[node] = natural.value.is_zero(),
+                ExprVariant::SExpr(scope) => {
+                    let nested = self.nested[node].expect("s-expressions have nodes");
+                    frames.push(Frame {
+                        scope,
+                        index: 0,
+                        first: Some(nested),
+                    });
+
+                    let head = self.head(frames);
+                    self.empty[node] = head == Some(Builtin::Add) && scope.exprs().len() == 1;
+                    for index in 0..scope.exprs().len() {
+                        let argument = match head {
+                            _ if index == 0 => Guard::None,
+                            Some(Builtin::Add) => Guard::Alternative,
+                            Some(Builtin::ZeroOrOne) => Guard::Always,
+                            _ => Guard::None,
+                        };
+
+                        self.edges[node].push((nested + index, guard(dereferenced, argument)));
+                    }
+
+                    self.add_edges(frames);
+                    frames.pop();
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The builtin at the head of the s-expression in the innermost
+    /// frame, if it refers to one directly.
+    fn head(&self, frames: &[Frame]) -> Option<Builtin> {
+        let scope = frames.last()?.scope;
+        let ExprVariant::Reference(reference) = &scope.exprs().first()?.base.variant else {
+            return None;
+        };
+
+        let ReferenceVariant::Resolved(reference) = reference.variant() else {
+            return None;
+        };
+
+        match self.find_target(frames, reference)?.0.base.variant {
+            ExprVariant::Builtin(builtin) => Some(builtin),
+            _ => None,
+        }
+    }
+
+    /// Finds the expression a reference in the innermost frame points
+    /// to, along with its node unless it's outside of the graph.
+    fn find_target<'a>(
+        &self,
+        frames: &[Frame<'a>],
+        reference: &ResolvedReference,
+    ) -> Option<(&'a Expr, Option<usize>)> {
+        let level = frames.len().checked_sub(reference.scope + 1)?;
+        let Frame {
+            scope,
+            index,
+            first,
+        } = frames[level];
+
+        let index = index.checked_add_signed(reference.offset)?;
+        let mut expr = scope.exprs().get(index)?;
+        let mut node = first.map(|first| first + index);
+        for &index in reference.path.iter() {
+            let ExprVariant::SExpr(scope) = &expr.base.variant else {
+                return None;
+            };
+
+            expr = scope.exprs().get(index)?;
+            node = node
+                .and_then(|node| self.nested[node])
+                .map(|first| first + index);
+        }
+
+        Some((expr, node))
+    }
+
+    /// Finds the strongly connected components that contain a cycle,
+    /// along with whether they're guarded.
+    ///
+    /// An unguarded cycle is its own component, even if it's part of a
+    /// larger guarded cycle.
+    fn cyclic_components(&self) -> Vec<(Vec<usize>, bool)> {
+        let all_edges = self
+            .edges
+            .iter()
+            .map(|edges| edges.iter().map(|(to, _)| *to).collect())
+            .collect::<Vec<_>>();
+
+        // A sum guards its alternatives if one of them can be picked to
+        // end the recursion, which no alternative in the same component
+        // can
+        let mut component_of = vec![None; self.edges.len()];
+        for (id, component) in strongly_connected(&all_edges).into_iter().enumerate() {
+            for node in component {
+                component_of[node] = Some(id);
+            }
+        }
+
+        let guarding = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(node, edges)| {
+                component_of[node].is_some()
+                    && edges.iter().any(|(to, guard)| {
+                        *guard == Guard::Alternative
+                            && !self.empty[*to]
+                            && component_of[*to] != component_of[node]
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let unguarded_edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(node, edges)| {
+                edges
+                    .iter()
+                    .filter(|(_, guard)| match guard {
+                        Guard::None => true,
+                        Guard::Always => false,
+                        Guard::Alternative => !guarding[node],
+                    })
+                    .map(|(to, _)| *to)
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        let mut components = Vec::new();
+        let mut unguarded = vec![false; self.edges.len()];
+        for component in strongly_connected(&unguarded_edges) {
+            for node in &component {
+                unguarded[*node] = true;
+            }
+
+            components.push((component, false));
+        }
+
+        for component in strongly_connected(&all_edges) {
+            if !component.iter().any(|node| unguarded[*node]) {
+                components.push((component, true));
+            }
+        }
+
+        components
+    }
+
+    fn cycle(&self, component: &[usize], guarded: bool) -> Cycle {
+        let mut references = component
+            .iter()
+            .filter_map(|node| self.references[*node].clone())
+            .collect::<Vec<_>>();
+
+        references.sort_by_key(|(_, span)| span.start);
+        Cycle {
+            file: references
+                .first()
+                .map(|(file, _)| *file)
+                .unwrap_or_default(),
+            references: references.into_iter().map(|(_, span)| span).collect(),
+            guarded,
+        }
+    }
+}
+
+fn guard(dereferenced: bool, guard: Guard) -> Guard {
+    match dereferenced {
+        true => Guard::Always,
+        false => guard,
+    }
+}
+
+/// Skips past any dereferences, returning whether there were any.
+fn unwrap_derefs(expr: &Expr) -> (&BaseExpr, bool) {
+    let mut base = &expr.base;
+    let mut dereferenced = false;
+    while let ExprVariant::Deref(inner) = &base.variant {
+        base = inner;
+        dereferenced = true;
+    }
+
+    (base, dereferenced)
+}
+
+/// Finds the strongly connected components that contain a cycle, using
+/// Tarjan's algorithm.
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut order = vec![None; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_order = 0;
+
+    for root in 0..edges.len() {
+        if order[root].is_some() {
+            continue;
+        }
+
+        // Iterative to support deeply nested scopes
+        let mut calls = vec![(root, 0)];
+        order[root] = Some(next_order);
+        low[root] = next_order;
+        next_order += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, next_edge)) = calls.last_mut() {
+            let node = *node;
+            if let Some(&to) = edges[node].get(*next_edge) {
+                *next_edge += 1;
+                match order[to] {
+                    None => {
+                        order[to] = Some(next_order);
+                        low[to] = next_order;
+                        next_order += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        calls.push((to, 0));
+                    }
+                    Some(to_order) if on_stack[to] => low[node] = low[node].min(to_order),
+                    Some(_) => (),
+                }
+
+                continue;
+            }
+
+            calls.pop();
+            if let Some((parent, _)) = calls.last() {
+                low[*parent] = low[*parent].min(low[node]);
+            }
+
+            if Some(low[node]) == order[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+
+                if component.len() > 1 || edges[node].contains(&node) {
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    components
+}
@@ -0,0 +1,400 @@
+use std::{collections::BTreeMap, fmt::Write, ops::Range};
+
+use crate::{
+    eval,
+    parser::{Error, ErrorVariant},
+    source::{FileId, SourceMap},
+};
+
+/// How a [Renderer] styles its output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    #[default]
+    Plain,
+
+    /// Colored with ANSI escape codes, for terminals.
+    Colored,
+}
+
+/// Renders errors as annotated snippets of their sources, in the style
+/// of rustc.
+///
+/// A [ScopeError](crate::ast::ScopeError) is rendered by converting it
+/// into an [Error] first, while [evaluation errors](eval::Error) have
+/// their own [render_eval](Self::render_eval).
+pub struct Renderer<'a> {
+    sources: &'a SourceMap,
+    style: Style,
+}
+
+struct Annotation {
+    span: Range<usize>,
+    primary: bool,
+    message: &'static str,
+}
+
+const RED: &str = "1;31";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+impl<'a> Renderer<'a> {
+    pub fn new(sources: &'a SourceMap) -> Self {
+        Self {
+            sources,
+            style: Style::default(),
+        }
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn render(&self, error: &Error) -> String {
+        let mut annotations = vec![Annotation {
+            span: error.span.clone(),
+            primary: true,
+            message: primary_message(&error.variant),
+        }];
+
+        annotations.extend(secondary_annotations(&error.variant));
+
+        let mut notes = Vec::new();
+        for label in &error.trace {
+            let note = format!("note: while parsing {label}");
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+
+        notes.extend(extra_notes(&error.variant));
+        let code = error.variant.code();
+        self.snippet(error.file, code, &error.to_string(), &annotations, notes)
+    }
+
+    /// Renders an error found while [evaluating](crate::eval::eval) a
+    /// program.
+    pub fn render_eval(&self, error: &eval::Error) -> String {
+        let annotation = Annotation {
+            span: error.span.clone(),
+            primary: true,
+            message: eval_message(&error.variant),
+        };
+
+        let code = error.variant.code();
+        let notes = eval_notes(&error.variant);
+        self.snippet(
+            error.file,
+            code,
+            &error.variant.to_string(),
+            &[annotation],
+            notes,
+        )
+    }
+
+    /// Renders annotations on the lines they're on, where the first
+    /// annotation is the primary one.
+    ///
+    /// Errors in files that aren't in the [SourceMap] are rendered without
+    /// a snippet.
+    fn snippet(
+        &self,
+        file: FileId,
+        code: &str,
+        message: &str,
+        annotations: &[Annotation],
+        notes: Vec<String>,
+    ) -> String {
+        let mut out = String::new();
+        self.paint(&mut out, RED, &format!("error[{code}]"));
+        self.paint(&mut out, BOLD, &format!(": {message}"));
+        out.push('\n');
+        let Some(file) = self.sources.try_get(file) else {
+            self.notes(&mut out, "", notes);
+            return out;
+        };
+
+        // Annotations by line, each with its column and width
+        let mut lines = BTreeMap::<_, Vec<_>>::new();
+        for annotation in annotations {
+            let start = file.location(annotation.span.start);
+            let end = file.location(annotation.span.end);
+            let end = if end.line == start.line {
+                end.column
+            } else {
+                file.line(start.line).chars().count()
+            };
+
+            let width = end.saturating_sub(start.column).max(1);
+            lines
+                .entry(start.line)
+                .or_default()
+                .push((start.column, width, annotation));
+        }
+
+        let start = file.location(annotations[0].span.start);
+        let gutter = " ".repeat((lines.keys().last().unwrap() + 1).to_string().len());
+        self.paint(&mut out, BLUE, &format!("{gutter}--> "));
+        writeln!(
+            out,
+            "{}:{}:{}",
+            file.path.display(),
+            start.line + 1,
+            start.column + 1
+        )
+        .unwrap();
+
+        self.paint(&mut out, BLUE, &format!("{gutter} |"));
+        out.push('\n');
+        let mut previous = None;
+        for (line, mut annotations) in lines {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                self.paint(&mut out, BLUE, "...");
+                out.push('\n');
+            }
+
+            previous = Some(line);
+            let number = format!("{:>width$} | ", line + 1, width = gutter.len());
+            self.paint(&mut out, BLUE, &number);
+            out.push_str(file.line(line));
+            out.push('\n');
+
+            annotations.sort_by_key(|(column, _, _)| *column);
+            for (column, width, annotation) in annotations {
+                self.paint(&mut out, BLUE, &format!("{gutter} | "));
+                out.push_str(&" ".repeat(column));
+                let (color, marker) = match annotation.primary {
+                    true => (RED, "^"),
+                    false => (BLUE, "-"),
+                };
+
+                let mut underline = marker.repeat(width);
+                if !annotation.message.is_empty() {
+                    underline = format!("{underline} {}", annotation.message);
+                }
+
+                self.paint(&mut out, color, &underline);
+                out.push('\n');
+            }
+        }
+
+        if !notes.is_empty() {
+            self.paint(&mut out, BLUE, &format!("{gutter} |"));
+            out.push('\n');
+        }
+
+        self.notes(&mut out, &gutter, notes);
+        out
+    }
+
+    fn notes(&self, out: &mut String, gutter: &str, notes: Vec<String>) {
+        for note in notes {
+            self.paint(out, BLUE, &format!("{gutter} = "));
+            out.push_str(&note);
+            out.push('\n');
+        }
+    }
+
+    fn paint(&self, out: &mut String, color: &str, text: &str) {
+        match self.style {
+            Style::Plain => out.push_str(text),
+            Style::Colored => write!(out, "\x1b[{color}m{text}\x1b[0m").unwrap(),
+        }
+    }
+}
+
+fn primary_message(variant: &ErrorVariant) -> &'static str {
+    match variant {
+        ErrorVariant::UnexpectedChar(Some(_)) => "unexpected character",
+        ErrorVariant::UnexpectedChar(None) => "expected more input",
+        ErrorVariant::EmptySymbol => "symbol without a name",
+        ErrorVariant::DuplicateLabel(_) => "labelled again here",
+        ErrorVariant::InvalidPath => "no label along this path",
+        ErrorVariant::InvalidValueRef => "not in a product or the base of a map",
+        ErrorVariant::InvalidSeparator => "separators go between digits",
+        ErrorVariant::NaturalOverflow => "",
+        ErrorVariant::UnresolvedSymbol(_) => "not labelled in any enclosing scope",
+        ErrorVariant::UnguardedCycle(_) => "recurses without a guard",
+        ErrorVariant::ImportNotFound => "not found relative to this file or the search path",
+        ErrorVariant::ImportCycle => "imports a file that's importing this file",
+        ErrorVariant::TrailingGarbage => "expected the end of the program",
+    }
+}
+
+fn secondary_annotations(variant: &ErrorVariant) -> Vec<Annotation> {
+    match variant {
+        ErrorVariant::DuplicateLabel(other_span) => vec![Annotation {
+            span: other_span.clone(),
+            primary: false,
+            message: "first labelled here",
+        }],
+        ErrorVariant::UnguardedCycle(other_spans) => other_spans
+            .iter()
+            .map(|span| Annotation {
+                span: span.clone(),
+                primary: false,
+                message: "through here",
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn extra_notes(variant: &ErrorVariant) -> Vec<String> {
+    match variant {
+        ErrorVariant::UnresolvedSymbol(Some(suggestion)) => {
+            vec![format!("help: a similar label is in scope: `{suggestion}`")]
+        }
+        ErrorVariant::UnguardedCycle(_) => vec![
+            "note: cycles have to pass through a dereference, an optional or a sum with another alternative"
+                .into(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn eval_message(variant: &eval::ErrorVariant) -> &'static str {
+    match variant {
+        eval::ErrorVariant::Bottom => "evaluates to `0`",
+        eval::ErrorVariant::AssertionFailed => "arguments have different numbers of states",
+        eval::ErrorVariant::Undecidable => "arguments can't be compared",
+        eval::ErrorVariant::Cycle => "depends on itself",
+        eval::ErrorVariant::UnresolvedSymbol(_) => "not labelled in any enclosing scope",
+        eval::ErrorVariant::InvalidReference => "doesn't point to an expression",
+        eval::ErrorVariant::UnknownFunction => "not a builtin",
+        eval::ErrorVariant::Unsupported => "can't be evaluated yet",
+        eval::ErrorVariant::Unencodable(_) => "not in the encoding of this text",
+    }
+}
+
+fn eval_notes(variant: &eval::ErrorVariant) -> Vec<String> {
+    match variant {
+        eval::ErrorVariant::Bottom => vec![
+            "note: products and the base of maps propagate `0`, unless a sum catches it".into(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chumsky::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser::parser;
+
+    fn render(source: &str, style: Style) -> String {
+        let mut sources = SourceMap::new();
+        sources.add("test.ari", source);
+        let (_, errors) = parser().parse_recovery(source);
+        let renderer = Renderer::new(&sources).with_style(style);
+        errors.iter().map(|err| renderer.render(err)).collect()
+    }
+
+    #[test]
+    pub fn duplicate_label() {
+        assert_eq!(
+            render(":byte 256\n:byte 128", Style::Plain),
+            "\
+error[E0003]: duplicate label
+ --> test.ari:2:1
+  |
+1 | :byte 256
+  | ----- first labelled here
+2 | :byte 128
+  | ^^^^^ labelled again here
+"
+        );
+    }
+
+    #[test]
+    pub fn unresolved_symbol() {
+        assert_eq!(
+            render(":list 1\n\n\n(+ lists)", Style::Plain),
+            "\
+error[E0008]: unresolved symbol
+ --> test.ari:4:4
+  |
+4 | (+ lists)
+  |    ^^^^^ not labelled in any enclosing scope
+  |
+  = help: a similar label is in scope: `list`
+"
+        );
+    }
+
+    #[test]
+    pub fn trace() {
+        assert_eq!(
+            render("(+ 1 :)", Style::Plain),
+            "\
+error[E0001]: expected `'` or a symbol, found `)`
+ --> test.ari:1:7
+  |
+1 | (+ 1 :)
+  |       ^ unexpected character
+  |
+  = note: while parsing symbol
+  = note: while parsing label
+  = note: while parsing labelled expression
+  = note: while parsing s-expression
+  = note: while parsing reference
+"
+        );
+    }
+
+    #[test]
+    pub fn colored() {
+        assert_eq!(
+            render("1_", Style::Colored),
+            "\
+\x1b[1;31merror[E0006]\x1b[0m\x1b[1m: invalid digit separator\x1b[0m
+\x1b[1;34m --> \x1b[0mtest.ari:1:2
+\x1b[1;34m  |\x1b[0m
+\x1b[1;34m1 | \x1b[0m1_
+\x1b[1;34m  | \x1b[0m \x1b[1;31m^ separators go between digits\x1b[0m
+\x1b[1;34m  |\x1b[0m
+\x1b[1;34m  = \x1b[0mnote: while parsing natural
+\x1b[1;34m  = \x1b[0mnote: while parsing reference
+"
+        );
+    }
+
+    #[test]
+    pub fn eval_error() {
+        let source = ":empty (* 256 (+))";
+        let mut sources = SourceMap::new();
+        sources.add("test.ari", source);
+        let scope = parser().parse(source).unwrap();
+        let errors = eval::eval(&scope);
+        let err = errors[0].as_ref().unwrap_err();
+
+        assert_eq!(
+            Renderer::new(&sources).render_eval(err),
+            "\
+error[E0101]: uncaught bottom type
+ --> test.ari:1:15
+  |
+1 | :empty (* 256 (+))
+  |               ^^^ evaluates to `0`
+  |
+  = note: products and the base of maps propagate `0`, unless a sum catches it
+"
+        );
+    }
+
+    #[test]
+    pub fn unknown_file() {
+        let (_, errors) = parser().parse_recovery("1_");
+        let sources = SourceMap::new();
+        assert_eq!(
+            Renderer::new(&sources).render(&errors[0]),
+            "\
+error[E0006]: invalid digit separator
+ = note: while parsing natural
+ = note: while parsing reference
+"
+        );
+    }
+}
@@ -0,0 +1,694 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, mem,
+    ops::Range,
+};
+
+use num_traits::{One, Pow, Zero};
+
+use crate::{
+    ast::{BaseExpr, ExprVariant, ReferenceVariant, ResolvedReference, Scope},
+    cycles::guarded_positions,
+    prelude::{prelude, Builtin},
+    source::FileId,
+    symbolic::Symbolic,
+    text::{EncodedText, Encoding},
+};
+
+/// Evaluates every expression in a scope resolved against the
+/// [prelude] to a normalized [Type].
+///
+/// Bottom types produced by products or the base of maps are reported
+/// as [ErrorVariant::Bottom], unless they're caught by another
+/// expression (like a sum). A reference back to an expression through a
+/// [guarded](crate::cycles::Cycle::guarded) cycle evaluates to
+/// [TypeVariant::Recursive], while other cycles are reported as
+/// [ErrorVariant::Cycle].
+pub fn eval(scope: &Scope) -> Box<[Result<Type, Error>]> {
+    let prelude = prelude();
+    let mut evaluator = Evaluator::new(scope);
+    (0..scope.exprs().len())
+        .map(|index| evaluator.eval_root(&prelude, scope, index))
+        .collect()
+}
+
+/// Evaluates the expression at `index` of a scope like [eval], along
+/// with only the expressions it references.
+///
+/// # Panics
+///
+/// Panics if there's no expression at `index`.
+pub fn eval_at(scope: &Scope, index: usize) -> Result<Type, Error> {
+    assert!(index < scope.exprs().len(), "no expression at {index}");
+    let prelude = prelude();
+    Evaluator::new(scope).eval_root(&prelude, scope, index)
+}
+
+/// A normalized type, along with the number of possible states it
+/// can hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    /// The number of possible states, or [None] if the type depends
+    /// on a runtime value or is recursive.
+    pub states: Option<Symbolic>,
+    pub variant: TypeVariant,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeVariant {
+    Natural,
+    Text(EncodedText),
+    Sum(Box<[Type]>),
+    Product(Box<[Type]>),
+    /// A map from the exponent (second) to the base (first).
+    Map(Box<Type>, Box<Type>),
+    /// A type evaluated from a runtime value.
+    Dependent,
+    /// A reference back to a type enclosing it, through a guarded
+    /// cycle.
+    Recursive,
+    Builtin(Builtin),
+}
+
+impl Type {
+    pub fn natural(states: impl Into<Symbolic>) -> Self {
+        Self {
+            states: Some(states.into()),
+            variant: TypeVariant::Natural,
+        }
+    }
+
+    pub fn text(text: EncodedText) -> Self {
+        let code_units = Symbolic::from(text.code_units.len() as u64);
+        Self {
+            states: Some(Symbolic::from(text.encoding.code_unit()).pow(code_units)),
+            variant: TypeVariant::Text(text),
+        }
+    }
+
+    pub fn dependent() -> Self {
+        Self {
+            states: None,
+            variant: TypeVariant::Dependent,
+        }
+    }
+
+    pub fn recursive() -> Self {
+        Self {
+            states: None,
+            variant: TypeVariant::Recursive,
+        }
+    }
+
+    pub fn builtin(builtin: Builtin) -> Self {
+        Self {
+            states: None,
+            variant: TypeVariant::Builtin(builtin),
+        }
+    }
+
+    /// Builds a sum type, flattening nested sums and removing bottom
+    /// types (the identity of sums).
+    pub fn sum(terms: impl IntoIterator<Item = Type>) -> Self {
+        let mut flattened = Vec::new();
+        for term in terms {
+            match term.variant {
+                TypeVariant::Sum(terms) => flattened.extend(terms.into_vec()),
+                _ if term.is_bottom() => (),
+                _ => flattened.push(term),
+            }
+        }
+
+        match flattened.len() {
+            0 => Self::natural(0u8),
+            1 => flattened.pop().unwrap(),
+            _ => Self {
+                states: flattened.iter().try_fold(Symbolic::zero(), |acc, term| {
+                    Some(acc + term.states.clone()?)
+                }),
+                variant: TypeVariant::Sum(flattened.into_boxed_slice()),
+            },
+        }
+    }
+
+    /// Builds a product type, flattening nested products and removing
+    /// unit types (the identity of products).
+    pub fn product(factors: impl IntoIterator<Item = Type>) -> Self {
+        let mut flattened = Vec::new();
+        for factor in factors {
+            match factor.variant {
+                TypeVariant::Product(factors) => flattened.extend(factors.into_vec()),
+                TypeVariant::Natural if factor.is_unit() => (),
+                _ => flattened.push(factor),
+            }
+        }
+
+        match flattened.len() {
+            0 => Self::natural(1u8),
+            1 => flattened.pop().unwrap(),
+            _ => Self {
+                states: flattened.iter().try_fold(Symbolic::one(), |acc, factor| {
+                    Some(acc * factor.states.clone()?)
+                }),
+                variant: TypeVariant::Product(flattened.into_boxed_slice()),
+            },
+        }
+    }
+
+    /// Builds a map type, merging nested maps with the power of a
+    /// power identity.
+    pub fn map(base: Type, exponent: Type) -> Self {
+        if exponent.is_bottom() {
+            return Self::natural(1u8);
+        }
+
+        if exponent.is_unit() {
+            return base;
+        }
+
+        match base.variant {
+            TypeVariant::Map(base, inner_exponent) => {
+                Self::map(*base, Self::product([*inner_exponent, exponent]))
+            }
+            variant => {
+                let base = Self {
+                    states: base.states,
+                    variant,
+                };
+
+                Self {
+                    states: base
+                        .states
+                        .clone()
+                        .zip(exponent.states.clone())
+                        .map(|(base, exponent)| base.pow(exponent)),
+                    variant: TypeVariant::Map(Box::new(base), Box::new(exponent)),
+                }
+            }
+        }
+    }
+
+    pub fn is_bottom(&self) -> bool {
+        self.states.as_ref().is_some_and(Zero::is_zero)
+    }
+
+    pub fn is_unit(&self) -> bool {
+        self.states.as_ref().is_some_and(One::is_one)
+    }
+
+    /// Checks if this type refers back to itself, so it has an unbounded
+    /// number of states.
+    pub fn is_recursive(&self) -> bool {
+        match &self.variant {
+            TypeVariant::Recursive => true,
+            TypeVariant::Sum(types) | TypeVariant::Product(types) => {
+                types.iter().any(Type::is_recursive)
+            }
+            TypeVariant::Map(base, exponent) => base.is_recursive() || exponent.is_recursive(),
+            _ => false,
+        }
+    }
+}
+
+/// Writes the type as an ari expression, like `(* 256 (+ 1 1))`. Types
+/// that depend on runtime values are written as `'<dependent>'`, and
+/// references back to a recursive type as `'<recursive>'`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (head, args) = match &self.variant {
+            TypeVariant::Natural => {
+                return match &self.states {
+                    Some(states) => states.fmt(f),
+                    None => f.write_str("'<dependent>'"),
+                }
+            }
+            TypeVariant::Text(text) => {
+                let quoted = format!("\"{}\"", text.decode().replace('"', "\"\""));
+                return match text.encoding {
+                    Encoding::Utf8 => f.write_str(&quoted),
+                    encoding => write!(f, "({} {quoted})", Builtin::Encoding(encoding).names()[0]),
+                };
+            }
+            TypeVariant::Sum(terms) => ("+", terms),
+            TypeVariant::Product(factors) => ("*", factors),
+            TypeVariant::Map(base, exponent) => return write!(f, "(^ {base} {exponent})"),
+            TypeVariant::Dependent => return f.write_str("'<dependent>'"),
+            TypeVariant::Recursive => return f.write_str("'<recursive>'"),
+            TypeVariant::Builtin(builtin) => return f.write_str(builtin.names()[0]),
+        };
+
+        write!(f, "({head}")?;
+        for arg in args.iter() {
+            write!(f, " {arg}")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// The file [span](Self::span) is in.
+    pub file: FileId,
+
+    pub span: Range<usize>,
+    pub variant: ErrorVariant,
+}
+
+impl Error {
+    pub fn bottom(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::Bottom,
+            span,
+        }
+    }
+
+    pub fn assertion_failed(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::AssertionFailed,
+            span,
+        }
+    }
+
+    pub fn undecidable(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::Undecidable,
+            span,
+        }
+    }
+
+    pub fn cycle(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::Cycle,
+            span,
+        }
+    }
+
+    pub fn unresolved_symbol(span: Range<usize>, name: impl Into<String>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::UnresolvedSymbol(name.into()),
+            span,
+        }
+    }
+
+    pub fn invalid_reference(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::InvalidReference,
+            span,
+        }
+    }
+
+    pub fn unknown_function(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::UnknownFunction,
+            span,
+        }
+    }
+
+    pub fn unsupported(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::Unsupported,
+            span,
+        }
+    }
+
+    pub fn unencodable(span: Range<usize>, found: char) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::Unencodable(found),
+            span,
+        }
+    }
+
+    pub fn in_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Checks if this error evaluates to the bottom type `0`, which
+    /// can be caught by other expressions.
+    pub fn is_bottom(&self) -> bool {
+        matches!(
+            self.variant,
+            ErrorVariant::Bottom | ErrorVariant::AssertionFailed
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorVariant {
+    /// A product or the base of a map is the bottom type `0`.
+    Bottom,
+    /// The arguments of an assertion have a different number of
+    /// states.
+    AssertionFailed,
+    /// The arguments of an assertion can't be compared.
+    Undecidable,
+    /// An expression depends on itself through a cycle that isn't
+    /// guarded.
+    Cycle,
+    UnresolvedSymbol(String),
+    InvalidReference,
+    UnknownFunction,
+    /// A builtin that can't be evaluated yet.
+    Unsupported,
+    Unencodable(char),
+}
+
+impl ErrorVariant {
+    /// A code identifying the kind of error, like `E0101`. Evaluation
+    /// errors are numbered from `E0101`, so they never overlap with
+    /// [parser errors](crate::parser::ErrorVariant::code).
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorVariant::Bottom => "E0101",
+            ErrorVariant::AssertionFailed => "E0102",
+            ErrorVariant::Undecidable => "E0103",
+            ErrorVariant::Cycle => "E0104",
+            ErrorVariant::UnresolvedSymbol(_) => "E0105",
+            ErrorVariant::InvalidReference => "E0106",
+            ErrorVariant::UnknownFunction => "E0107",
+            ErrorVariant::Unsupported => "E0108",
+            ErrorVariant::Unencodable(_) => "E0109",
+        }
+    }
+}
+
+impl fmt::Display for ErrorVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorVariant::Bottom => write!(f, "uncaught bottom type"),
+            ErrorVariant::AssertionFailed => write!(f, "assertion failed"),
+            ErrorVariant::Undecidable => write!(f, "undecidable assertion"),
+            ErrorVariant::Cycle => write!(f, "expression depends on itself"),
+            ErrorVariant::UnresolvedSymbol(name) => write!(f, "unresolved symbol `{name}`"),
+            ErrorVariant::InvalidReference => write!(f, "invalid reference"),
+            ErrorVariant::UnknownFunction => write!(f, "unknown function"),
+            ErrorVariant::Unsupported => write!(f, "unsupported builtin"),
+            ErrorVariant::Unencodable(c) => write!(f, "unencodable character `{c}`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame<'a> {
+    scope: &'a Scope,
+    index: usize,
+}
+
+#[derive(Default)]
+struct Evaluator<'a> {
+    /// The lexical scopes enclosing the expression being evaluated.
+    stack: Vec<Frame<'a>>,
+
+    /// Results by the position of the expression, as the index of each
+    /// frame on the stack. Scopes can be shared between positions with
+    /// different encoding contexts, so expressions can't be identified
+    /// by their address.
+    results: HashMap<Box<[usize]>, Result<Type, Error>>,
+
+    /// The positions being evaluated, along with how many were being
+    /// evaluated before them.
+    visiting: HashMap<Box<[usize]>, usize>,
+
+    /// The positions within the root scope that are only part of
+    /// guarded cycles, and can be referenced while being evaluated.
+    guarded: HashSet<Box<[usize]>>,
+
+    /// The depth in [visiting](Self::visiting) of the outermost position
+    /// a [recursive type](TypeVariant::Recursive) referred back to,
+    /// since the expression being evaluated started, or [usize::MAX] if
+    /// none did.
+    recursion: usize,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(root: &Scope) -> Self {
+        Self {
+            guarded: guarded_positions(root),
+            ..Self::default()
+        }
+    }
+
+    /// Evaluates an expression of a root scope resolved against
+    /// `prelude`.
+    fn eval_root(
+        &mut self,
+        prelude: &'a Scope,
+        scope: &'a Scope,
+        index: usize,
+    ) -> Result<Type, Error> {
+        // The root scope is placed right after the prelude
+        let outer = Frame {
+            scope: prelude,
+            index: prelude.exprs().len(),
+        };
+
+        self.stack = vec![outer, Frame { scope, index }];
+        self.eval_top()
+    }
+
+    /// Evaluates the expression at the top of the stack.
+    fn eval_top(&mut self) -> Result<Type, Error> {
+        let frame = *self.stack.last().expect("at least one frame");
+        let base = &frame.scope.exprs()[frame.index].base;
+        let key = self
+            .stack
+            .iter()
+            .map(|frame| frame.index)
+            .collect::<Box<_>>();
+        if let Some(result) = self.results.get(&key) {
+            return result.clone();
+        }
+
+        if let Some(&depth) = self.visiting.get(&key) {
+            // The first frame is the prelude
+            if !self.guarded.contains(&key[1..]) {
+                return Err(Error::cycle(base.span.clone()).in_file(base.file));
+            }
+
+            self.recursion = self.recursion.min(depth);
+            return Ok(Type::recursive());
+        }
+
+        let depth = self.visiting.len();
+        self.visiting.insert(key.clone(), depth);
+        let outer_recursion = mem::replace(&mut self.recursion, usize::MAX);
+        let result = self.eval_base(base);
+        self.visiting.remove(&key);
+
+        // A recursive type refers back to whichever expression is being
+        // evaluated, so results containing one can't be reused elsewhere
+        if self.recursion == usize::MAX {
+            self.results.insert(key, result.clone());
+        }
+
+        if self.recursion >= depth {
+            self.recursion = outer_recursion;
+        } else {
+            self.recursion = self.recursion.min(outer_recursion);
+        }
+
+        result
+    }
+
+    fn eval_base(&mut self, base: &'a BaseExpr) -> Result<Type, Error> {
+        match &base.variant {
+            ExprVariant::Natural(natural) => Ok(Type::natural(natural.value.clone())),
+            ExprVariant::Text(text) => {
+                self.encoding().encode(text).map(Type::text).map_err(|err| {
+                    Error::unencodable(base.span.clone(), err.found).in_file(base.file)
+                })
+            }
+            ExprVariant::ValueRef(_) => Ok(Type::natural(1u8)),
+            ExprVariant::Deref(_) => Ok(Type::dependent()),
+            ExprVariant::Builtin(builtin) => Ok(Type::builtin(*builtin)),
+            ExprVariant::Reference(reference) => {
+                let resolved = match reference.variant() {
+                    ReferenceVariant::Unresolved(unresolved) => {
+                        return Err(Error::unresolved_symbol(
+                            unresolved.symbol.span.clone(),
+                            unresolved.symbol.name.clone(),
+                        )
+                        .in_file(base.file));
+                    }
+                    ReferenceVariant::Resolved(resolved) => resolved.clone(),
+                };
+
+                self.eval_reference(&resolved, base)
+            }
+            ExprVariant::SExpr(scope) => self.eval_sexpr(scope, base),
+        }
+    }
+
+    fn eval_reference(
+        &mut self,
+        reference: &ResolvedReference,
+        base: &BaseExpr,
+    ) -> Result<Type, Error> {
+        let invalid = || Error::invalid_reference(base.span.clone()).in_file(base.file);
+        let depth = self
+            .stack
+            .len()
+            .checked_sub(reference.scope + 1)
+            .ok_or_else(invalid)?;
+
+        let mut stack = self.stack[..=depth].to_vec();
+        let mut frame = stack.pop().ok_or_else(invalid)?;
+        frame.index = frame
+            .index
+            .checked_add_signed(reference.offset)
+            .filter(|&index| index < frame.scope.exprs().len())
+            .ok_or_else(invalid)?;
+
+        for &index in reference.path.iter() {
+            let ExprVariant::SExpr(scope) = &frame.scope.exprs()[frame.index].base.variant else {
+                return Err(invalid());
+            };
+
+            if index >= scope.exprs().len() {
+                return Err(invalid());
+            }
+
+            stack.push(frame);
+            frame = Frame { scope, index };
+        }
+
+        stack.push(frame);
+        let stack = mem::replace(&mut self.stack, stack);
+        let result = self.eval_top();
+        self.stack = stack;
+        result
+    }
+
+    fn eval_sexpr(&mut self, scope: &'a Scope, base: &BaseExpr) -> Result<Type, Error> {
+        let Some(head) = scope.exprs().first() else {
+            return Err(Error::unknown_function(base.span.clone()).in_file(base.file));
+        };
+
+        let builtin = match self.eval_head(scope)? {
+            Type {
+                variant: TypeVariant::Builtin(builtin),
+                ..
+            } => builtin,
+            _ => {
+                return Err(Error::unknown_function(head.base.span.clone()).in_file(head.base.file))
+            }
+        };
+
+        let mut args = (1..scope.exprs().len()).map(|index| {
+            self.stack.push(Frame { scope, index });
+            let result = self.eval_top();
+            self.stack.pop();
+            (&scope.exprs()[index].base, result)
+        });
+
+        match builtin {
+            Builtin::Add => Ok(Type::sum(
+                args.map(|(_, result)| catch(result))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            Builtin::Multiply | Builtin::Encoding(_) => Ok(Type::product(
+                args.map(propagate).collect::<Result<Vec<_>, _>>()?,
+            )),
+            Builtin::Exponentiate => {
+                let Some(base) = args.next() else {
+                    return Ok(Type::natural(1u8));
+                };
+
+                let base = propagate(base)?;
+                args.try_fold(base, |base, (_, exponent)| {
+                    Ok(Type::map(base, catch(exponent)?))
+                })
+            }
+            Builtin::Assert => {
+                let args = args
+                    .map(|(_, result)| catch(result))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for pair in args.windows(2) {
+                    let (Some(states), Some(other_states)) = (&pair[0].states, &pair[1].states)
+                    else {
+                        // Dependent types are asserted at runtime
+                        continue;
+                    };
+
+                    match states.partial_cmp(other_states) {
+                        Some(ordering) if ordering.is_eq() => (),
+                        Some(_) => {
+                            return Err(
+                                Error::assertion_failed(base.span.clone()).in_file(base.file)
+                            )
+                        }
+                        None => {
+                            return Err(Error::undecidable(base.span.clone()).in_file(base.file))
+                        }
+                    }
+                }
+
+                Ok(args
+                    .into_iter()
+                    .last()
+                    .unwrap_or_else(|| Type::natural(1u8)))
+            }
+            _ => Err(Error::unsupported(head.base.span.clone()).in_file(head.base.file)),
+        }
+    }
+
+    /// Evaluates the head of a symbolic expression in the current
+    /// context.
+    fn eval_head(&mut self, scope: &'a Scope) -> Result<Type, Error> {
+        self.stack.push(Frame { scope, index: 0 });
+        let result = self.eval_top();
+        self.stack.pop();
+        result
+    }
+
+    /// The encoding context of the expression being evaluated, from
+    /// the closest text encoding macro.
+    fn encoding(&mut self) -> Encoding {
+        // Only frames above the prelude and the root scope are
+        // symbolic expressions
+        for depth in (2..self.stack.len()).rev() {
+            let scope = self.stack[depth].scope;
+            let stack = self.stack[..depth].to_vec();
+            let stack = mem::replace(&mut self.stack, stack);
+            let head = self.eval_head(scope);
+            self.stack = stack;
+
+            if let Ok(Type {
+                variant: TypeVariant::Builtin(Builtin::Encoding(encoding)),
+                ..
+            }) = head
+            {
+                return encoding;
+            }
+        }
+
+        Encoding::default()
+    }
+}
+
+/// Catches bottom types, for contexts that don't propagate them.
+fn catch(result: Result<Type, Error>) -> Result<Type, Error> {
+    match result {
+        Err(err) if err.is_bottom() => Ok(Type::natural(0u8)),
+        result => result,
+    }
+}
+
+/// Reports bottom types, for contexts that propagate them.
+fn propagate((base, result): (&BaseExpr, Result<Type, Error>)) -> Result<Type, Error> {
+    match result {
+        Ok(ty) if ty.is_bottom() => Err(Error::bottom(base.span.clone()).in_file(base.file)),
+        result => result,
+    }
+}
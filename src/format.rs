@@ -0,0 +1,654 @@
+use std::fmt;
+
+use crate::{
+    ast::{
+        BaseExpr, Expr, ExprVariant, Label, Reference, ReferenceVariant, ResolvedReference, Scope,
+    },
+    cst::{Cst, Node, Token, TokenKind},
+    intern::Name,
+    parser::symbol_char,
+    prelude::prelude,
+};
+
+/// Options for laying out a [Doc].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// The number of columns lines are kept within, where possible.
+    pub width: usize,
+
+    /// The number of spaces each level of nesting is indented by.
+    pub indent: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            indent: 2,
+        }
+    }
+}
+
+/// A document that can be laid out in different ways, depending on how
+/// much fits within a line.
+///
+/// Each [group](Doc::Group) is laid out on one line if it fits, and
+/// otherwise has its lines broken, as in Wadler's "A prettier printer".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Doc {
+    Text(String),
+
+    /// A space, or a line break if its group is broken.
+    Line,
+
+    /// Nothing, or a line break if its group is broken.
+    SoftLine,
+
+    /// A line break that breaks every group it's in.
+    HardLine,
+
+    Concat(Vec<Doc>),
+
+    /// Indents the lines broken within a document by another level.
+    Nest(Box<Doc>),
+
+    Group(Box<Doc>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    pub fn nest(self) -> Self {
+        Self::Nest(Box::new(self))
+    }
+
+    pub fn group(self) -> Self {
+        Self::Group(Box::new(self))
+    }
+
+    /// Lays out this document, breaking as few groups as possible to
+    /// keep lines within [Config::width].
+    pub fn pretty(&self, config: &Config) -> String {
+        let mut out = String::new();
+        let mut column = 0;
+
+        // Indentation is only written before text, so blank lines don't
+        // end up with trailing whitespace
+        let mut pending_indent = None;
+        let mut stack = vec![(0, Mode::Break, self)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(text) => {
+                    if let Some(indent) = pending_indent.take() {
+                        out.extend(std::iter::repeat_n(' ', indent));
+                    }
+
+                    out.push_str(text);
+                    column = match text.rsplit_once('\n') {
+                        Some((_, last)) => last.chars().count(),
+                        None => column + text.chars().count(),
+                    };
+                }
+                Doc::Line if mode == Mode::Flat => {
+                    out.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine if mode == Mode::Flat => (),
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    out.push('\n');
+                    pending_indent = Some(indent);
+                    column = indent;
+                }
+                Doc::Concat(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+                Doc::Nest(doc) => stack.push((indent + config.indent, mode, doc)),
+                Doc::Group(doc) => {
+                    let fits = mode == Mode::Flat
+                        || fits(config.width as isize - column as isize, doc, &stack);
+
+                    let mode = if fits { Mode::Flat } else { Mode::Break };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Checks if a group fits in the rest of the line when laid out flat,
+/// along with whatever follows it up to the next line break.
+fn fits(mut width: isize, group: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut rest = rest.iter().rev().map(|(_, mode, doc)| (*mode, *doc));
+    let mut stack = vec![(Mode::Flat, group)];
+    loop {
+        if width < 0 {
+            return false;
+        }
+
+        let Some((mode, doc)) = stack.pop().or_else(|| rest.next()) else {
+            return true;
+        };
+
+        match doc {
+            Doc::Text(text) => match text.split_once('\n') {
+                Some((first, _)) => return width >= first.chars().count() as isize,
+                None => width -= text.chars().count() as isize,
+            },
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::SoftLine if mode == Mode::Flat => (),
+            Doc::Line | Doc::SoftLine => return true,
+
+            // A line break within the group can't be laid out flat, but
+            // one after it just ends the line
+            Doc::HardLine => return mode == Mode::Break,
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Nest(doc) | Doc::Group(doc) => stack.push((mode, doc)),
+        }
+    }
+}
+
+/// Formats a root scope that was resolved against the
+/// [prelude](crate::prelude::prelude), one expression per line.
+///
+/// Parsed naturals are written the way they were in the source, while
+/// other naturals are written in decimal, or as a power of two when
+/// they're too large. Use [source] to also keep comments.
+pub fn scope(scope: &Scope) -> Doc {
+    let prelude = prelude();
+    let mut printer = Printer {
+        frames: vec![(Some(&prelude), prelude.exprs().len()), (Some(scope), 0)],
+    };
+
+    let docs = printer.exprs(scope.exprs());
+    Doc::Concat(separated(docs, Doc::HardLine))
+}
+
+/// Formats an expression on its own, as if it was the only expression
+/// of a root scope.
+///
+/// References to other expressions of its scope can't be named, so
+/// they're formatted as `'<unnamed>'`.
+pub fn expr(expr: &Expr) -> Doc {
+    let prelude = prelude();
+    let mut printer = Printer {
+        frames: vec![(Some(&prelude), prelude.exprs().len()), (None, 0)],
+    };
+
+    printer.expr(expr)
+}
+
+/// Formats source code, keeping its comments, blank lines between
+/// expressions, and the way each token was written, like the radix of
+/// naturals.
+///
+/// Only whitespace is changed, so the formatted source parses to the
+/// same [Scope] as long as the original did.
+pub fn source(cst: &Cst) -> Doc {
+    let units = units(cst.tokens(), cst.nodes());
+    if units.is_empty() {
+        return Doc::Concat(Vec::new());
+    }
+
+    let (mut docs, _) = sequence(cst.tokens(), units, Doc::HardLine);
+    docs.push(Doc::HardLine);
+    Doc::Concat(docs)
+}
+
+/// Formats the scope with [scope], printing naturals in decimal.
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&scope(self).pretty(&Config::default()))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&expr(self).pretty(&Config::default()))
+    }
+}
+
+/// Formats expressions, naming resolved references by looking up the
+/// labels of the scopes enclosing them.
+struct Printer<'a> {
+    /// The scopes enclosing the expression being formatted, from
+    /// outermost to innermost, each with the index of the expression
+    /// within it that's being formatted. Scopes that aren't known are
+    /// [None].
+    frames: Vec<(Option<&'a Scope>, usize)>,
+}
+
+impl<'a> Printer<'a> {
+    fn exprs(&mut self, exprs: &'a [Expr]) -> Vec<Doc> {
+        exprs
+            .iter()
+            .enumerate()
+            .map(|(index, expr)| {
+                self.frames.last_mut().expect("at least one scope").1 = index;
+                self.expr(expr)
+            })
+            .collect()
+    }
+
+    fn expr(&mut self, expr: &'a Expr) -> Doc {
+        let labels = expr.labels.iter().map(label).collect::<Vec<_>>();
+        labelled(labels, self.base(&expr.base))
+    }
+
+    fn base(&mut self, base: &'a BaseExpr) -> Doc {
+        match &base.variant {
+            ExprVariant::Natural(natural) => Doc::text(match &natural.source {
+                Some(source) => source.to_string(),
+                None => natural.value.to_string(),
+            }),
+            ExprVariant::Text(text) => Doc::text(quote(text, '"')),
+            ExprVariant::Reference(reference) => Doc::text(self.reference(reference)),
+            ExprVariant::ValueRef(reference) => {
+                Doc::text(format!("${}", self.reference(reference)))
+            }
+            ExprVariant::Deref(inner) => Doc::Concat(vec![Doc::text("@"), self.base(inner)]),
+            ExprVariant::SExpr(scope) => {
+                self.frames.push((Some(scope), 0));
+                let docs = self.exprs(scope.exprs());
+                self.frames.pop();
+                list(docs)
+            }
+            ExprVariant::Builtin(builtin) => Doc::text(builtin.names()[0]),
+        }
+    }
+
+    fn reference(&self, reference: &Reference) -> String {
+        match reference.variant() {
+            ReferenceVariant::Unresolved(unresolved) => {
+                let mut text = "\\".repeat(unresolved.skip);
+                text.push_str(&symbol(
+                    &unresolved.symbol.name,
+                    unresolved.symbol.doc.as_deref(),
+                ));
+
+                text.extend(unresolved.path.iter().map(label));
+                text
+            }
+            ReferenceVariant::Resolved(resolved) => self
+                .resolved_reference(resolved)
+                .unwrap_or_else(|| "'<unnamed>'".into()),
+        }
+    }
+
+    /// Names a resolved reference by one of its target's labels,
+    /// skipping as few labels shadowing it as possible.
+    fn resolved_reference(&self, resolved: &ResolvedReference) -> Option<String> {
+        let depth = (self.frames.len() - 1).checked_sub(resolved.scope)?;
+        let (scope, index) = self.frames[depth];
+        let scope = scope?;
+        let index = index.checked_add_signed(resolved.offset)?;
+        let target = scope.exprs().get(index)?;
+
+        let (skip, name) = target
+            .labels
+            .iter()
+            .filter(|label| scope.index_of(&label.name) == Some(index))
+            .map(|label| {
+                let skip = self.frames[depth + 1..]
+                    .iter()
+                    .filter(|(scope, _)| {
+                        scope.is_some_and(|scope| scope.index_of(&label.name).is_some())
+                    })
+                    .count();
+
+                (skip, &label.name)
+            })
+            .min_by_key(|(skip, _)| *skip)?;
+
+        let mut text = "\\".repeat(skip);
+        text.push_str(&symbol(name, None));
+
+        let mut base = &target.base;
+        for &index in &resolved.path {
+            let ExprVariant::SExpr(scope) = &base.variant else {
+                return None;
+            };
+
+            let child = scope.exprs().get(index)?;
+            let label = child
+                .labels
+                .iter()
+                .find(|label| scope.index_of(&label.name) == Some(index))?;
+
+            text.push(':');
+            text.push_str(&symbol(&label.name, None));
+            base = &child.base;
+        }
+
+        Some(text)
+    }
+}
+
+/// Formats parenthesized expressions, breaking every expression after
+/// the first onto its own line if they don't fit on one.
+fn list(docs: Vec<Doc>) -> Doc {
+    if docs.is_empty() {
+        return Doc::text("()");
+    }
+
+    Doc::Concat(vec![
+        Doc::text("("),
+        Doc::Concat(separated(docs, Doc::Line)).nest(),
+        Doc::SoftLine,
+        Doc::text(")"),
+    ])
+    .group()
+}
+
+/// Puts labels before an expression, on their own line if they don't
+/// fit on one together.
+fn labelled(labels: Vec<String>, base: Doc) -> Doc {
+    if labels.is_empty() {
+        return base;
+    }
+
+    Doc::Concat(vec![Doc::Text(labels.join(" ")), Doc::Line, base]).group()
+}
+
+fn separated(docs: Vec<Doc>, separator: Doc) -> Vec<Doc> {
+    let mut separated = Vec::with_capacity(docs.len() * 2);
+    for doc in docs {
+        if !separated.is_empty() {
+            separated.push(separator.clone());
+        }
+
+        separated.push(doc);
+    }
+
+    separated
+}
+
+fn label(label: &Label) -> String {
+    format!(":{}", symbol(&label.name, label.doc.as_deref()))
+}
+
+/// Formats a symbol, quoting it if it has documentation or couldn't be
+/// parsed otherwise.
+fn symbol(name: &Name, doc: Option<&str>) -> String {
+    let name = name.as_str();
+    let plain = doc.is_none()
+        && name.chars().all(|c| symbol_char(&c))
+        && !name.starts_with(|c: char| c == '\\' || c.is_ascii_digit())
+        && !name.starts_with("#|")
+        && !name.is_empty();
+
+    match doc {
+        _ if plain => name.into(),
+        Some(doc) => quote(&format!("{name} {doc}"), '\''),
+        None => quote(name, '\''),
+    }
+}
+
+fn quote(text: &str, quote: char) -> String {
+    let escaped = text.replace(quote, &format!("{quote}{quote}"));
+    format!("{quote}{escaped}{quote}")
+}
+
+/// A comment or an expression in the source.
+enum Unit<'a> {
+    Comment(&'a Token),
+    Expr {
+        labels: Vec<&'a [Node]>,
+        base: Option<&'a [Node]>,
+    },
+}
+
+/// Groups nodes into comments and expressions, each with the number of
+/// line breaks before it. Each label and base expression is made up of
+/// tokens and lists that aren't separated by trivia.
+fn units<'a>(tokens: &'a [Token], nodes: &'a [Node]) -> Vec<(usize, Unit<'a>)> {
+    let mut units = Vec::new();
+    let mut newlines = 0;
+
+    // The start of the run of nodes without trivia between them
+    let mut start = None;
+    for (index, node) in nodes.iter().enumerate() {
+        let token = match node {
+            Node::Token(token) if tokens[*token].kind.is_trivia() => &tokens[*token],
+            _ => {
+                start.get_or_insert(index);
+                continue;
+            }
+        };
+
+        if let Some(start) = start.take() {
+            push_atom(tokens, &mut units, newlines, &nodes[start..index]);
+            newlines = 0;
+        }
+
+        match token.kind {
+            TokenKind::Whitespace => newlines += token.text.matches('\n').count(),
+            _ => {
+                units.push((newlines, Unit::Comment(token)));
+                newlines = 0;
+            }
+        }
+    }
+
+    if let Some(start) = start {
+        push_atom(tokens, &mut units, newlines, &nodes[start..]);
+    }
+
+    units
+}
+
+/// Adds a run of nodes without trivia between them, as another label or
+/// the base of the labels before it.
+fn push_atom<'a>(
+    tokens: &'a [Token],
+    units: &mut Vec<(usize, Unit<'a>)>,
+    newlines: usize,
+    atom: &'a [Node],
+) {
+    let kind = |node: &Node| match node {
+        Node::Token(token) => Some(tokens[*token].kind),
+        Node::List { .. } => None,
+    };
+
+    let is_label = atom.first().and_then(kind) == Some(TokenKind::Colon)
+        && atom.last().and_then(kind) != Some(TokenKind::Colon)
+        && atom.iter().all(|node| {
+            matches!(
+                kind(node),
+                Some(TokenKind::Colon | TokenKind::Symbol | TokenKind::QuotedSymbol)
+            )
+        });
+
+    if let Some((
+        _,
+        Unit::Expr {
+            labels,
+            base: base @ None,
+        },
+    )) = units.last_mut()
+    {
+        match is_label {
+            true => labels.push(atom),
+            false => *base = Some(atom),
+        }
+
+        return;
+    }
+
+    let (labels, base) = match is_label {
+        true => (vec![atom], None),
+        false => (Vec::new(), Some(atom)),
+    };
+
+    units.push((newlines, Unit::Expr { labels, base }));
+}
+
+/// Lays out units one after another, keeping comments on the same line
+/// as they were and up to one blank line between units. Also returns
+/// whether the last unit is a line comment, which has to be followed by
+/// a line break.
+fn sequence(tokens: &[Token], units: Vec<(usize, Unit)>, separator: Doc) -> (Vec<Doc>, bool) {
+    let mut docs = Vec::new();
+    let mut previous: Option<TokenKind> = None;
+    let mut first = true;
+    for (newlines, unit) in units {
+        let comment = match &unit {
+            Unit::Comment(token) => Some(token.kind),
+            Unit::Expr { .. } => None,
+        };
+
+        if !first {
+            match previous {
+                Some(TokenKind::LineComment) => docs.push(Doc::HardLine),
+                _ if newlines == 0 && (comment.is_some() || previous.is_some()) => {
+                    docs.push(Doc::text(" "))
+                }
+                _ if newlines > 1 => docs.push(Doc::HardLine),
+                _ => docs.push(separator.clone()),
+            }
+
+            if newlines > 1 {
+                docs.push(Doc::HardLine);
+            }
+        }
+
+        docs.push(match unit {
+            Unit::Comment(token) => Doc::text(token.text.trim_end()),
+            Unit::Expr { labels, base } => {
+                let labels = labels
+                    .into_iter()
+                    .map(|atom| {
+                        atom.iter()
+                            .map(|node| match node {
+                                Node::Token(token) => tokens[*token].text.as_str(),
+                                Node::List { .. } => unreachable!("labels are only tokens"),
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                match base {
+                    Some(base) => labelled(labels, atom_doc(tokens, base)),
+                    None => Doc::Text(labels.join(" ")),
+                }
+            }
+        });
+
+        previous = comment;
+        first = false;
+    }
+
+    (docs, previous == Some(TokenKind::LineComment))
+}
+
+fn atom_doc(tokens: &[Token], atom: &[Node]) -> Doc {
+    Doc::Concat(
+        atom.iter()
+            .map(|node| match node {
+                Node::Token(token) => Doc::text(&tokens[*token].text),
+                Node::List {
+                    open,
+                    children,
+                    close,
+                } => {
+                    let units = units(tokens, children);
+                    if units.is_empty() && close.is_some() {
+                        return Doc::text("()");
+                    }
+
+                    let (docs, line_comment) = sequence(tokens, units, Doc::Line);
+                    Doc::Concat(vec![
+                        Doc::text(&tokens[*open].text),
+                        Doc::Concat(docs).nest(),
+                        match line_comment {
+                            true => Doc::HardLine,
+                            false => Doc::SoftLine,
+                        },
+                        Doc::text(close.map_or("", |close| tokens[close].text.as_str())),
+                    ])
+                    .group()
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn words(words: &[&str]) -> Doc {
+        Doc::Concat(vec![
+            Doc::text("("),
+            Doc::Concat(separated(
+                words.iter().map(|word| Doc::text(*word)).collect(),
+                Doc::Line,
+            ))
+            .nest(),
+            Doc::SoftLine,
+            Doc::text(")"),
+        ])
+        .group()
+    }
+
+    #[test]
+    pub fn flat_when_it_fits() {
+        let config = Config {
+            width: 11,
+            ..Config::default()
+        };
+
+        assert_eq!(words(&["+", "1", "2", "3"]).pretty(&config), "(+ 1 2 3)");
+    }
+
+    #[test]
+    pub fn breaks_when_it_doesnt_fit() {
+        let config = Config {
+            width: 8,
+            indent: 4,
+        };
+
+        assert_eq!(
+            words(&["+", "1", "2", "3"]).pretty(&config),
+            "(+\n    1\n    2\n    3\n)"
+        );
+    }
+
+    #[test]
+    pub fn hard_lines_break_groups() {
+        let doc = Doc::Concat(vec![
+            Doc::text("a"),
+            Doc::Concat(vec![
+                Doc::Line,
+                Doc::text("b"),
+                Doc::HardLine,
+                Doc::text("c"),
+            ])
+            .nest()
+            .group(),
+        ]);
+
+        assert_eq!(doc.pretty(&Config::default()), "a\n  b\n  c");
+    }
+
+    #[test]
+    pub fn no_trailing_whitespace() {
+        let doc = Doc::Concat(vec![
+            Doc::text("a"),
+            Doc::Concat(vec![Doc::HardLine, Doc::HardLine, Doc::text("b")]).nest(),
+        ]);
+
+        assert_eq!(doc.pretty(&Config::default()), "a\n\n  b");
+    }
+}
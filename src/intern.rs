@@ -0,0 +1,240 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{Arc, LazyLock, PoisonError, RwLock},
+};
+
+/// The name of a [Label](crate::ast::Label) or
+/// [Symbol](crate::ast::Symbol), interned so that equal names share one
+/// allocation and compare and hash like integers.
+///
+/// Names are reference counted, and the interner drops its own
+/// reference once nothing else refers to a name anymore, so unused
+/// names are freed.
+#[derive(Clone)]
+pub struct Name(Arc<str>);
+
+struct Interner {
+    names: HashSet<Arc<str>>,
+
+    /// The number of names to reach before dropping the names that
+    /// only the interner refers to, so that doing so is amortized
+    /// constant time.
+    purge_at: usize,
+}
+
+/// Names are mostly looked up after being interned once, so lookups
+/// only share the lock.
+static INTERNER: LazyLock<RwLock<Interner>> = LazyLock::new(|| RwLock::new(Interner::new()));
+
+const MIN_PURGE_AT: usize = 1024;
+
+impl Name {
+    /// Interns `name`, reusing the existing [Name] if there is one.
+    pub fn new(name: &str) -> Self {
+        // Interning never leaves the table inconsistent, even when it
+        // panics
+        let interner = INTERNER.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(name) = interner.names.get(name) {
+            return Self(name.clone());
+        }
+
+        drop(interner);
+        INTERNER
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .intern(name)
+    }
+
+    /// Looks up the [Name] of a string without interning it.
+    pub fn get(name: &str) -> Option<Self> {
+        INTERNER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Interner {
+    fn new() -> Self {
+        Self {
+            names: HashSet::new(),
+            purge_at: MIN_PURGE_AT,
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> Name {
+        if let Some(name) = self.names.get(name) {
+            return Name(name.clone());
+        }
+
+        if self.names.len() >= self.purge_at {
+            self.purge();
+        }
+
+        let interned = Arc::<str>::from(name);
+        self.names.insert(interned.clone());
+        Name(interned)
+    }
+
+    /// Looks up a name that something other than the interner still
+    /// refers to.
+    fn get(&self, name: &str) -> Option<Name> {
+        self.names
+            .get(name)
+            .filter(|name| Arc::strong_count(name) > 1)
+            .cloned()
+            .map(Name)
+    }
+
+    /// Drops the names that only the interner refers to.
+    fn purge(&mut self) {
+        // A name that only the interner refers to can only be cloned
+        // through it, so it can't come back to life while purging
+        self.names.retain(|name| Arc::strong_count(name) > 1);
+        self.purge_at = (self.names.len() * 2).max(MIN_PURGE_AT);
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<u8>().hash(state)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<&String> for Name {
+    fn from(name: &String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.as_str().to_owned()
+    }
+}
+
+impl From<&Name> for String {
+    fn from(name: &Name) -> Self {
+        name.as_str().to_owned()
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Names are ordered by their strings, not by when they were interned.
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.as_str().cmp(other.as_str())
+        }
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    pub fn interns_once() {
+        let name = Name::new("intern-once");
+        assert_eq!(Name::new("intern-once"), name);
+        assert_eq!(Name::from(String::from("intern-once")), name);
+        assert_eq!(name.as_str(), "intern-once");
+        assert_eq!(name, "intern-once");
+    }
+
+    #[test]
+    pub fn get_doesnt_intern() {
+        assert_eq!(Name::get("never-interned"), None);
+        let name = Name::new("interned-by-get");
+        assert_eq!(Name::get("interned-by-get"), Some(name));
+    }
+
+    #[test]
+    pub fn ordered_by_string() {
+        let b = Name::new("order-b");
+        let a = Name::new("order-a");
+        assert!(a < b);
+        assert_eq!(format!("{a} {b:?}"), "order-a \"order-b\"");
+    }
+
+    #[test]
+    pub fn frees_unused_names() {
+        let mut interner = Interner::new();
+        let name = interner.intern("freed");
+        assert_eq!(interner.get("freed"), Some(name.clone()));
+        drop(name);
+        assert_eq!(interner.get("freed"), None);
+
+        for i in 0..MIN_PURGE_AT * 4 {
+            interner.intern(&format!("freed-{i}"));
+        }
+
+        assert!(interner.names.len() < MIN_PURGE_AT * 2);
+    }
+}
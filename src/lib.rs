@@ -1,4 +1,16 @@
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 #[rustfmt::skip] pub mod ast;
+#[rustfmt::skip] pub mod cst;
+#[rustfmt::skip] pub mod cycles;
+#[rustfmt::skip] pub mod diagnostic;
+#[rustfmt::skip] pub mod eval;
+#[rustfmt::skip] pub mod format;
+#[rustfmt::skip] pub mod intern;
+#[rustfmt::skip] pub mod module;
 #[rustfmt::skip] pub mod natural;
 #[rustfmt::skip] pub mod parser;
+#[rustfmt::skip] pub mod prelude;
+#[rustfmt::skip] pub mod radix;
+#[rustfmt::skip] pub mod source;
+#[rustfmt::skip] pub mod symbolic;
+#[rustfmt::skip] pub mod text;
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    fs, io, iter,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use chumsky::Parser;
+
+use crate::{
+    ast::{Expr, ExprVariant, Scope},
+    parser::{self, Error},
+    source::{FileId, SourceMap},
+};
+
+/// A file loaded by a [Loader], along with the errors found in it. Its
+/// path and source are in the loader's [SourceMap].
+#[derive(Debug)]
+pub struct File {
+    /// The resolved root scope, unless the file couldn't be parsed.
+    pub scope: Option<Scope>,
+
+    pub errors: Vec<Error>,
+    loaded: bool,
+}
+
+/// Loads programs that are split across multiple files.
+///
+/// Files import other files with `(import "path")` expressions in their
+/// root scope, which are replaced with the root scope of the imported
+/// file. Labelling an import makes the labels in it available through
+/// paths, like `png:chunk:ihdr`.
+///
+/// Paths are relative to the importing file, falling back to each
+/// directory in the search path, and `.ari` is added to paths without
+/// an extension. Each file is only loaded once, no matter how often it's
+/// imported.
+#[derive(Debug, Default)]
+pub struct Loader {
+    search_path: Vec<PathBuf>,
+    sources: SourceMap,
+    files: Vec<File>,
+    file_from_path: HashMap<PathBuf, FileId>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_search_path(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.search_path.push(dir.into());
+        self
+    }
+
+    /// Loads a file along with everything it imports, unless it was
+    /// already loaded.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref().canonicalize()?;
+        if let Some(id) = self.file_from_path.get(&path) {
+            return Ok(*id);
+        }
+
+        let source = fs::read_to_string(&path)?;
+        Ok(self.load_file(path, source))
+    }
+
+    /// Loads a program that isn't stored in a file (like from stdin),
+    /// resolving its imports as if it was stored at `path`.
+    pub fn load_source(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> FileId {
+        self.load_file(path.into(), source.into())
+    }
+
+    pub fn file(&self, id: FileId) -> &File {
+        &self.files[id.index()]
+    }
+
+    /// The paths and sources of the loaded files.
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Every error found in the loaded files, with the file they're in.
+    pub fn errors(&self) -> impl Iterator<Item = &Error> {
+        self.files.iter().flat_map(|file| &file.errors)
+    }
+
+    fn load_file(&mut self, path: PathBuf, source: String) -> FileId {
+        let id = self.sources.add(path.clone(), source);
+        let (exprs, mut errors) = parser::module().parse_recovery(&*self.sources.get(id).source);
+        self.file_from_path.insert(path, id);
+        self.files.push(File {
+            scope: None,
+            errors: Vec::new(),
+            loaded: false,
+        });
+
+        let scope = exprs.map(|mut exprs| {
+            for expr in exprs.iter_mut() {
+                expr.base.set_file(id);
+                let Some((span, path)) = import_path(expr) else {
+                    continue;
+                };
+
+                let path = path.to_owned();
+                let scope = self.import(id, &path, span).unwrap_or_else(|err| {
+                    errors.push(err);
+                    Scope::default()
+                });
+
+                expr.base.variant = ExprVariant::SExpr(scope);
+            }
+
+            parser::resolve_root(exprs, id, true, &mut |err| errors.push(err))
+        });
+
+        let file = &mut self.files[id.index()];
+        file.scope = scope;
+        file.errors = errors.into_iter().map(|err| err.in_file(id)).collect();
+        file.loaded = true;
+        id
+    }
+
+    fn import(&mut self, from: FileId, path: &str, span: Range<usize>) -> Result<Scope, Error> {
+        let path = self
+            .find(from, path)
+            .ok_or_else(|| Error::import_not_found(span.clone()))?;
+
+        let id = match self.file_from_path.get(&path) {
+            Some(id) => *id,
+            None => {
+                let source =
+                    fs::read_to_string(&path).map_err(|_| Error::import_not_found(span.clone()))?;
+
+                self.load_file(path, source)
+            }
+        };
+
+        let file = self.file(id);
+        if !file.loaded {
+            return Err(Error::import_cycle(span));
+        }
+
+        Ok(file.scope.clone().unwrap_or_default().nest())
+    }
+
+    fn find(&self, from: FileId, path: &str) -> Option<PathBuf> {
+        let mut path = PathBuf::from(path);
+        if path.extension().is_none() {
+            path.set_extension("ari");
+        }
+
+        let dir = self
+            .sources
+            .get(from)
+            .path
+            .parent()
+            .unwrap_or(Path::new(""));
+        iter::once(dir)
+            .chain(self.search_path.iter().map(PathBuf::as_path))
+            .find_map(|dir| dir.join(&path).canonicalize().ok())
+    }
+}
+
+/// The span and path of an `(import "path")` expression.
+fn import_path(expr: &Expr) -> Option<(Range<usize>, &str)> {
+    let ExprVariant::SExpr(scope) = &expr.base.variant else {
+        return None;
+    };
+
+    match scope.exprs() {
+        [head, path] if head.base.is_symbol("import") => match &path.base.variant {
+            ExprVariant::Text(text) => Some((path.base.span.clone(), text)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -1,90 +1,413 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Add, Mul},
+};
+
 use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Max number of bits we're willing to materialize in a [BigUint].
+/// Larger values stay [symbolic](crate::symbolic::Symbolic), so that
+/// evaluating them never takes more than a few milliseconds.
+pub const MAX_UNALIGNED_BITS: u64 = 1 << 20;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The number of possible states in a type.
+///
+/// Powers of two are always represented by the number of bytes or
+/// bits they span, so that aligned types never need [BigUint]
+/// arithmetic. [Natural::ByteAligned] is preferred over
+/// [Natural::BitAligned] when both apply, and [Natural::Unaligned]
+/// never holds a power of two. Naturals built from the variants
+/// directly still compare and hash by value.
+#[derive(Debug, Clone)]
 pub enum Natural {
     Unaligned(BigUint),
     ByteAligned(usize),
+    BitAligned(usize),
+}
+
+impl Natural {
+    pub fn zero() -> Self {
+        Self::Unaligned(BigUint::zero())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Self::Unaligned(value) if value.is_zero())
+    }
+
+    pub fn one() -> Self {
+        Self::ByteAligned(0)
+    }
+
+    pub fn is_one(&self) -> bool {
+        *self == Self::ByteAligned(0)
+    }
+
+    /// Builds a natural with `2^bits` states.
+    pub fn from_bits(bits: usize) -> Self {
+        match bits % 8 {
+            0 => Self::ByteAligned(bits / 8),
+            _ => Self::BitAligned(bits),
+        }
+    }
+
+    /// Builds a natural from digits in the given radix, ordered from
+    /// most significant to least significant.
+    ///
+    /// Digits are accumulated in machine words, only falling back to
+    /// [BigUint] arithmetic for naturals that don't fit in a [u64].
+    /// Returns [None] if the radix is less than 2, or if a digit isn't
+    /// less than the radix.
+    pub fn from_digits(radix: u32, digits: &[u8]) -> Option<Self> {
+        let radix = u64::from(radix);
+        if radix < 2 || digits.iter().any(|&digit| u64::from(digit) >= radix) {
+            return None;
+        }
+
+        let accumulate = |digits: &[u8]| {
+            digits
+                .iter()
+                .fold(0u64, |acc, &digit| acc * radix + u64::from(digit))
+        };
+
+        // Max number of digits that always fit in a u64
+        let chunk_len = u64::MAX.ilog(radix) as usize;
+        let (first, rest) = digits.split_at(match digits.len() % chunk_len {
+            0 => chunk_len.min(digits.len()),
+            len => len,
+        });
+
+        let first = accumulate(first);
+        if rest.is_empty() {
+            return Some(Self::from(first));
+        }
+
+        let chunk_base = radix.pow(chunk_len as u32);
+        let mut value = BigUint::from(first);
+        for chunk in rest.chunks_exact(chunk_len) {
+            value *= chunk_base;
+            value += accumulate(chunk);
+        }
+
+        Some(Self::from(value))
+    }
+
+    pub fn to_usize(&self) -> Option<usize> {
+        match self {
+            Self::Unaligned(value) => value.to_usize(),
+            Self::ByteAligned(bytes) => 1usize.checked_shl(bytes.checked_mul(8)?.try_into().ok()?),
+            Self::BitAligned(bits) => 1usize.checked_shl((*bits).try_into().ok()?),
+        }
+    }
+
+    /// The number of bits needed to encode every state, or [None] if it
+    /// can't be addressed.
+    pub fn bit_width(&self) -> Option<usize> {
+        match self {
+            Self::Unaligned(value) if value.is_zero() => Some(0),
+            Self::Unaligned(value) => (value - 1u8).bits().try_into().ok(),
+            Self::ByteAligned(bytes) => bytes.checked_mul(8),
+            Self::BitAligned(bits) => Some(*bits),
+        }
+    }
+
+    /// The base 2 logarithm of a power of two, or [None] if it's not a
+    /// power of two.
+    pub fn exact_log2(&self) -> Option<usize> {
+        match self {
+            Self::Unaligned(_) => None,
+            Self::ByteAligned(bytes) => bytes.checked_mul(8),
+            Self::BitAligned(bits) => Some(*bits),
+        }
+    }
+
+    /// The base 2 logarithm rounded down, or [None] if it's zero.
+    pub fn checked_ilog2(&self) -> Option<u64> {
+        match self {
+            Self::Unaligned(value) => value.bits().checked_sub(1),
+            Self::ByteAligned(bytes) => (*bytes as u64).checked_mul(8),
+            Self::BitAligned(bits) => Some(*bits as u64),
+        }
+    }
+
+    /// The base 2 logarithm rounded down, in a type that can't
+    /// overflow for aligned naturals.
+    fn wide_log2(&self) -> Option<u128> {
+        match self {
+            Self::Unaligned(value) => value.bits().checked_sub(1).map(u128::from),
+            Self::ByteAligned(bytes) => Some(*bytes as u128 * 8),
+            Self::BitAligned(bits) => Some(*bits as u128),
+        }
+    }
+
+    /// The base 2 logarithm of a power of two in any representation, in
+    /// a type that can't overflow.
+    fn wide_exact_log2(&self) -> Option<u128> {
+        match self {
+            Self::Unaligned(value) if value.count_ones() == 1 => {
+                value.trailing_zeros().map(u128::from)
+            }
+            Self::Unaligned(_) => None,
+            aligned => aligned.wide_log2(),
+        }
+    }
+
+    /// Adds two naturals, returning [None] if the result can't be
+    /// represented, or if computing it would materialize a [BigUint] of
+    /// more than [MAX_UNALIGNED_BITS].
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(other.clone());
+        }
+
+        if other.is_zero() {
+            return Some(self.clone());
+        }
+
+        match (self.exact_log2(), other.exact_log2()) {
+            (Some(bits), Some(other_bits)) if bits == other_bits => {
+                Some(Self::from_bits(bits.checked_add(1)?))
+            }
+            _ => Some(Self::from(self.to_big_uint()? + other.to_big_uint()?)),
+        }
+    }
+
+    /// Subtracts two naturals, returning [None] if `other` is greater
+    /// than `self`, or if computing the result would materialize a
+    /// [BigUint] of more than [MAX_UNALIGNED_BITS].
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        match self.cmp(other) {
+            Ordering::Less => None,
+            Ordering::Equal => Some(Self::zero()),
+            Ordering::Greater if other.is_zero() => Some(self.clone()),
+            Ordering::Greater => match (self.exact_log2(), other.exact_log2()) {
+                (Some(bits), Some(other_bits)) if bits == other_bits + 1 => {
+                    Some(Self::from_bits(other_bits))
+                }
+                _ => Some(Self::from(self.to_big_uint()? - other.to_big_uint()?)),
+            },
+        }
+    }
+
+    /// Multiplies two naturals, returning [None] if the result can't
+    /// be represented, or if computing it would materialize a [BigUint]
+    /// of more than [MAX_UNALIGNED_BITS].
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() || other.is_zero() {
+            return Some(Self::zero());
+        }
+
+        match (self, other) {
+            (Self::Unaligned(value), Self::Unaligned(other_value)) => {
+                check_bits(value.bits().checked_add(other_value.bits())?)?;
+                Some(Self::from(value * other_value))
+            }
+            (Self::Unaligned(value), aligned) | (aligned, Self::Unaligned(value)) => {
+                let bits = aligned.exact_log2()?;
+                check_bits(value.bits().checked_add(bits as u64)?)?;
+                Some(Self::Unaligned(value << bits))
+            }
+            (aligned, other_aligned) => Some(Self::from_bits(
+                aligned
+                    .exact_log2()?
+                    .checked_add(other_aligned.exact_log2()?)?,
+            )),
+        }
+    }
+
+    /// Raises a natural to the power of `exponent`, returning [None] if
+    /// the result can't be represented, or if computing it would
+    /// materialize a [BigUint] of more than [MAX_UNALIGNED_BITS].
+    ///
+    /// Powers of two never build a [BigUint].
+    pub fn checked_pow(&self, exponent: &Self) -> Option<Self> {
+        if exponent.is_zero() || self.is_one() {
+            return Some(Self::one());
+        }
+
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+
+        let exponent = exponent.to_usize()?;
+        match self {
+            Self::Unaligned(value) => {
+                check_bits(value.bits().checked_mul(exponent as u64)?)?;
+                Some(Self::Unaligned(num_traits::pow(value.clone(), exponent)))
+            }
+            aligned => Some(Self::from_bits(
+                aligned.exact_log2()?.checked_mul(exponent)?,
+            )),
+        }
+    }
+
+    /// Converts the natural into a [BigUint], returning [None] if an
+    /// aligned natural has more than [MAX_UNALIGNED_BITS].
+    pub fn to_big_uint(&self) -> Option<BigUint> {
+        match self {
+            Self::Unaligned(value) => Some(value.clone()),
+            aligned => {
+                let bits = aligned.exact_log2()?;
+                check_bits((bits as u64).checked_add(1)?)?;
+                Some(BigUint::one() << bits)
+            }
+        }
+    }
+}
+
+fn check_bits(bits: u64) -> Option<()> {
+    (bits <= MAX_UNALIGNED_BITS).then_some(())
+}
+
+impl PartialEq for Natural {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Natural {}
+
+/// Hashes the value, so that equal naturals hash the same no matter
+/// how they're represented.
+impl Hash for Natural {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match (self.wide_exact_log2(), self) {
+            (Some(bits), _) => bits.hash(state),
+            (None, Self::Unaligned(value)) => value.hash(state),
+            (None, _) => unreachable!("aligned naturals are powers of two"),
+        }
+    }
+}
+
+impl PartialOrd for Natural {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Natural {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Unaligned(value), Self::Unaligned(other_value)) => value.cmp(other_value),
+            (Self::Unaligned(value), aligned) => cmp_unaligned(value, aligned),
+            (aligned, Self::Unaligned(value)) => cmp_unaligned(value, aligned).reverse(),
+            (aligned, other_aligned) => aligned.wide_log2().cmp(&other_aligned.wide_log2()),
+        }
+    }
+}
+
+/// Compares an unaligned natural to an aligned natural without
+/// materializing the aligned natural.
+fn cmp_unaligned(value: &BigUint, aligned: &Natural) -> Ordering {
+    let Some(ilog2) = value.bits().checked_sub(1) else {
+        // Only unaligned naturals can be zero
+        return Ordering::Less;
+    };
+
+    match u128::from(ilog2).cmp(&aligned.wide_log2().unwrap_or_default()) {
+        Ordering::Equal if value.count_ones() == 1 => Ordering::Equal,
+        Ordering::Equal => Ordering::Greater,
+        ordering => ordering,
+    }
+}
+
+/// Writes the natural in decimal, except for powers of two that don't
+/// fit in a [u64], which are written as `2^bits` so that they're never
+/// materialized.
+impl fmt::Display for Natural {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits = match self {
+            Self::Unaligned(value) => return value.fmt(f),
+            Self::ByteAligned(bytes) => *bytes as u128 * 8,
+            Self::BitAligned(bits) => *bits as u128,
+        };
+
+        match bits {
+            0..=63 => (1u64 << bits).fmt(f),
+            bits => write!(f, "2^{bits}"),
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if [Natural::checked_add] returns [None], including when
+/// computing the sum would go over [MAX_UNALIGNED_BITS].
+impl Add for Natural {
+    type Output = Natural;
+
+    fn add(self, other: Self) -> Self::Output {
+        &self + &other
+    }
+}
+
+impl Add<&Natural> for &Natural {
+    type Output = Natural;
+
+    fn add(self, other: &Natural) -> Self::Output {
+        self.checked_add(other).expect("natural overflow")
+    }
+}
+
+/// # Panics
+///
+/// Panics if [Natural::checked_mul] returns [None], including when
+/// computing the product would go over [MAX_UNALIGNED_BITS].
+impl Mul for Natural {
+    type Output = Natural;
+
+    fn mul(self, other: Self) -> Self::Output {
+        &self * &other
+    }
+}
+
+impl Mul<&Natural> for &Natural {
+    type Output = Natural;
+
+    fn mul(self, other: &Natural) -> Self::Output {
+        self.checked_mul(other).expect("natural overflow")
+    }
 }
 
 impl From<u8> for Natural {
     fn from(value: u8) -> Self {
-        Natural::ByteAligned(match value {
-            0x1 => 0,
-            value => return Natural::Unaligned(BigUint::from(value)),
-        })
+        Self::from(u64::from(value))
     }
 }
 
 impl From<u16> for Natural {
-    #[cfg_attr(coverage_nightly, coverage(off))]
     fn from(value: u16) -> Self {
-        Natural::ByteAligned(match value {
-            0x1 => 0,
-            0x100 => 1,
-            value => return Natural::Unaligned(BigUint::from(value)),
-        })
+        Self::from(u64::from(value))
     }
 }
 
 impl From<u32> for Natural {
-    #[cfg_attr(coverage_nightly, coverage(off))]
     fn from(value: u32) -> Self {
-        Natural::ByteAligned(match value {
-            0x1 => 0,
-            0x100 => 1,
-            0x10000 => 2,
-            0x1000000 => 3,
-            value => return Natural::Unaligned(BigUint::from(value)),
-        })
+        Self::from(u64::from(value))
     }
 }
 
 impl From<u64> for Natural {
-    #[cfg_attr(coverage_nightly, coverage(off))]
     fn from(value: u64) -> Self {
-        Natural::ByteAligned(match value {
-            0x1 => 0,
-            0x100 => 1,
-            0x10000 => 2,
-            0x1000000 => 3,
-            0x100000000 => 4,
-            0x10000000000 => 5,
-            0x1000000000000 => 6,
-            0x100000000000000 => 7,
-            value => return Natural::Unaligned(BigUint::from(value)),
-        })
+        if value.is_power_of_two() {
+            Self::from_bits(value.trailing_zeros() as usize)
+        } else {
+            Self::Unaligned(BigUint::from(value))
+        }
     }
 }
 
 impl From<BigUint> for Natural {
     fn from(value: BigUint) -> Self {
-        let mut digits = value.iter_u64_digits();
-        while let Some(msd) = digits.next_back() {
-            let mut bytes = match msd {
-                0x0 => continue,
-                0x1 => 0,
-                0x100 => 1,
-                0x10000 => 2,
-                0x1000000 => 3,
-                0x100000000 => 4,
-                0x10000000000 => 5,
-                0x1000000000000 => 6,
-                0x100000000000000 => 7,
-                _ => return Self::Unaligned(value),
-            };
-
-            for lsd in digits.by_ref() {
-                if lsd != 0 {
-                    return Self::Unaligned(value);
-                }
-
-                bytes += 8;
-            }
-
-            return Self::ByteAligned(bytes);
+        match value.trailing_zeros() {
+            Some(bits) if value.count_ones() == 1 => match bits.try_into() {
+                Ok(bits) => Self::from_bits(bits),
+                Err(_) => Self::Unaligned(value),
+            },
+            _ => Self::Unaligned(value),
         }
-
-        Self::Unaligned(value)
     }
 }
 
@@ -118,6 +441,315 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn from_digits() {
+        assert_eq!(
+            Natural::from_digits(10, &[2, 5, 6]),
+            Some(Natural::ByteAligned(1))
+        );
+        assert_eq!(Natural::from_digits(10, &[]), Some(Natural::zero()));
+    }
+
+    #[test]
+    pub fn from_invalid_digits() {
+        assert_eq!(Natural::from_digits(0, &[0]), None);
+        assert_eq!(Natural::from_digits(1, &[0]), None);
+        assert_eq!(Natural::from_digits(10, &[1, 10]), None);
+        assert_eq!(Natural::from_digits(2, &[2]), None);
+    }
+
+    #[test]
+    pub fn from_big_digits() {
+        let digits = "87112285931760246646623899502532662132735"
+            .bytes()
+            .map(|digit| digit - b'0')
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            Natural::from_digits(10, &digits),
+            Some(Natural::Unaligned(
+                BigUint::from_str("87112285931760246646623899502532662132735").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    pub fn from_multiple_chunks_of_digits() {
+        let mut digits = vec![1];
+        digits.extend([0; 32]);
+        assert_eq!(
+            Natural::from_digits(16, &digits),
+            Some(Natural::ByteAligned(16))
+        );
+    }
+
+    #[test]
+    pub fn pow_byte_aligned() {
+        assert_eq!(
+            Natural::ByteAligned(1).checked_pow(&Natural::from(4096u16)),
+            Some(Natural::ByteAligned(4096))
+        );
+    }
+
+    #[test]
+    pub fn pow_power_of_two() {
+        assert_eq!(
+            Natural::from(2u8).checked_pow(&Natural::from(4096u16)),
+            Some(Natural::ByteAligned(512))
+        );
+        assert_eq!(
+            Natural::from(2u8).checked_pow(&Natural::from(4097u16)),
+            Some(Natural::BitAligned(4097))
+        );
+    }
+
+    #[test]
+    pub fn pow_unaligned() {
+        assert_eq!(
+            Natural::from(3u8).checked_pow(&Natural::from(3u8)),
+            Some(Natural::from(27u8))
+        );
+    }
+
+    #[test]
+    pub fn pow_zero() {
+        assert_eq!(
+            Natural::from(0u8).checked_pow(&Natural::from(0u8)),
+            Some(Natural::ByteAligned(0))
+        );
+        assert_eq!(
+            Natural::from(0u8).checked_pow(&Natural::from(2u8)),
+            Some(Natural::from(0u8))
+        );
+    }
+
+    #[test]
+    pub fn pow_overflow() {
+        assert_eq!(
+            Natural::ByteAligned(2).checked_pow(&Natural::from(u64::MAX)),
+            None
+        );
+    }
+
+    #[test]
+    pub fn bit_aligned() {
+        assert_eq!(Natural::from(8u8), Natural::BitAligned(3));
+        assert_eq!(Natural::from(4096u16), Natural::BitAligned(12));
+    }
+
+    #[test]
+    pub fn big_bit_aligned() {
+        assert_eq!(
+            Natural::from(BigUint::one() << 129u8),
+            Natural::BitAligned(129)
+        );
+    }
+
+    #[test]
+    pub fn into_big_uint() {
+        assert_eq!(
+            Natural::BitAligned(12).to_big_uint(),
+            Some(BigUint::from(4096u16))
+        );
+        assert_eq!(
+            Natural::ByteAligned(2).to_big_uint(),
+            Some(BigUint::from(65536u32))
+        );
+        assert_eq!(Natural::from(27u8).to_big_uint(), Some(BigUint::from(27u8)));
+        assert_eq!(Natural::ByteAligned(usize::MAX).to_big_uint(), None);
+        assert_eq!(Natural::BitAligned(usize::MAX).to_big_uint(), None);
+    }
+
+    #[test]
+    pub fn bit_width() {
+        assert_eq!(Natural::from(0u8).bit_width(), Some(0));
+        assert_eq!(Natural::from(1u8).bit_width(), Some(0));
+        assert_eq!(Natural::from(3u8).bit_width(), Some(2));
+        assert_eq!(Natural::from(8u8).bit_width(), Some(3));
+        assert_eq!(Natural::from(9u8).bit_width(), Some(4));
+        assert_eq!(Natural::from(256u16).bit_width(), Some(8));
+        assert_eq!(Natural::ByteAligned(usize::MAX).bit_width(), None);
+    }
+
+    #[test]
+    pub fn add_aligned() {
+        assert_eq!(
+            Natural::from(128u8).checked_add(&Natural::from(128u8)),
+            Some(Natural::ByteAligned(1))
+        );
+    }
+
+    #[test]
+    pub fn add_unaligned() {
+        assert_eq!(
+            Natural::from(3u8).checked_add(&Natural::ByteAligned(1)),
+            Some(Natural::from(259u16))
+        );
+        assert_eq!(
+            Natural::from(0u8).checked_add(&Natural::from(5u8)),
+            Some(Natural::from(5u8))
+        );
+    }
+
+    #[test]
+    pub fn mul_aligned() {
+        assert_eq!(
+            Natural::ByteAligned(1).checked_mul(&Natural::ByteAligned(1)),
+            Some(Natural::ByteAligned(2))
+        );
+        assert_eq!(
+            Natural::BitAligned(3).checked_mul(&Natural::BitAligned(5)),
+            Some(Natural::ByteAligned(1))
+        );
+    }
+
+    #[test]
+    pub fn mul_unaligned() {
+        assert_eq!(
+            Natural::from(3u8).checked_mul(&Natural::ByteAligned(1)),
+            Some(Natural::from(768u16))
+        );
+        assert_eq!(
+            Natural::from(3u8).checked_mul(&Natural::from(5u8)),
+            Some(Natural::from(15u8))
+        );
+        assert_eq!(
+            Natural::from(3u8).checked_mul(&Natural::from(0u8)),
+            Some(Natural::zero())
+        );
+    }
+
+    #[test]
+    pub fn pow_natural() {
+        assert_eq!(
+            Natural::ByteAligned(1).checked_pow(&Natural::from(4u8)),
+            Some(Natural::ByteAligned(4))
+        );
+        assert_eq!(
+            Natural::from(1u8).checked_pow(&Natural::ByteAligned(usize::MAX)),
+            Some(Natural::one())
+        );
+    }
+
+    #[test]
+    pub fn huge_aligned_overflow() {
+        let huge = Natural::ByteAligned(usize::MAX);
+        assert_eq!(huge.checked_add(&Natural::from(3u8)), None);
+        assert_eq!(huge.checked_mul(&Natural::from(3u8)), None);
+        assert_eq!(huge.checked_mul(&Natural::ByteAligned(1)), None);
+        assert_eq!(huge.checked_pow(&Natural::from(2u8)), None);
+        assert_eq!(
+            huge.checked_add(&Natural::zero()),
+            Some(Natural::ByteAligned(usize::MAX))
+        );
+    }
+
+    #[test]
+    pub fn unaligned_budget() {
+        let big = Natural::Unaligned((BigUint::one() << (MAX_UNALIGNED_BITS - 2)) + 1u8);
+        assert!(big.checked_add(&big).is_some());
+        assert_eq!(big.checked_mul(&big), None);
+        assert_eq!(big.checked_pow(&Natural::from(2u8)), None);
+        assert_eq!(
+            Natural::BitAligned(MAX_UNALIGNED_BITS as usize).checked_add(&Natural::from(3u8)),
+            None
+        );
+    }
+
+    #[test]
+    pub fn checked_sub() {
+        assert_eq!(
+            Natural::ByteAligned(1).checked_sub(&Natural::from(128u8)),
+            Some(Natural::BitAligned(7))
+        );
+        assert_eq!(
+            Natural::ByteAligned(1).checked_sub(&Natural::from(1u8)),
+            Some(Natural::from(255u8))
+        );
+        assert_eq!(
+            Natural::from(5u8).checked_sub(&Natural::from(5u8)),
+            Some(Natural::zero())
+        );
+        assert_eq!(Natural::from(3u8).checked_sub(&Natural::from(5u8)), None);
+    }
+
+    #[test]
+    pub fn cmp() {
+        assert!(Natural::from(0u8) < Natural::from(1u8));
+        assert!(Natural::from(255u8) < Natural::ByteAligned(1));
+        assert!(Natural::from(257u16) > Natural::ByteAligned(1));
+        assert!(Natural::BitAligned(9) > Natural::ByteAligned(1));
+        assert!(Natural::ByteAligned(usize::MAX) > Natural::from(257u16));
+        assert!(Natural::ByteAligned(usize::MAX) > Natural::BitAligned(usize::MAX));
+        assert!(Natural::from(5u8) > Natural::from(3u8));
+    }
+
+    #[test]
+    pub fn operators() {
+        assert_eq!(
+            Natural::from(128u8) + Natural::from(128u8),
+            Natural::ByteAligned(1)
+        );
+        assert_eq!(
+            &Natural::from(3u8) + &Natural::ByteAligned(1),
+            Natural::from(259u16)
+        );
+        assert_eq!(
+            Natural::ByteAligned(1) * Natural::ByteAligned(1),
+            Natural::ByteAligned(2)
+        );
+        assert_eq!(
+            &Natural::from(3u8) * &Natural::from(5u8),
+            Natural::from(15u8)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "natural overflow")]
+    pub fn operator_overflow() {
+        let _ = Natural::ByteAligned(usize::MAX) * Natural::ByteAligned(1);
+    }
+
+    #[test]
+    pub fn eq_by_value() {
+        let equal = [
+            Natural::ByteAligned(1),
+            Natural::BitAligned(8),
+            Natural::Unaligned(BigUint::from(256u16)),
+        ];
+
+        for (a, b) in equal.iter().zip(equal.iter().skip(1)) {
+            assert_eq!(a, b);
+            assert_eq!(a.cmp(b), Ordering::Equal);
+            assert_eq!(hash(a), hash(b));
+        }
+
+        assert_ne!(Natural::BitAligned(9), Natural::ByteAligned(1));
+        assert_ne!(
+            Natural::Unaligned(BigUint::from(257u16)),
+            Natural::ByteAligned(1)
+        );
+        assert_eq!(
+            Natural::Unaligned(BigUint::from(1u8)).cmp(&Natural::BitAligned(1)),
+            Ordering::Less
+        );
+    }
+
+    fn hash(natural: &Natural) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        natural.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    pub fn log2() {
+        assert_eq!(Natural::from(0u8).checked_ilog2(), None);
+        assert_eq!(Natural::from(255u8).checked_ilog2(), Some(7));
+        assert_eq!(Natural::ByteAligned(1).checked_ilog2(), Some(8));
+        assert_eq!(Natural::from(255u8).exact_log2(), None);
+        assert_eq!(Natural::BitAligned(3).exact_log2(), Some(3));
+    }
+
     #[test]
     pub fn big_byte_aligned() {
         assert_eq!(
@@ -125,4 +757,23 @@ mod tests {
             Natural::ByteAligned(17)
         );
     }
+
+    #[test]
+    pub fn display() {
+        assert_eq!(Natural::from(255u8).to_string(), "255");
+        assert_eq!(Natural::from(0u8).to_string(), "0");
+        assert_eq!(Natural::BitAligned(63).to_string(), "9223372036854775808");
+        assert_eq!(Natural::ByteAligned(8).to_string(), "2^64");
+        assert_eq!(
+            Natural::ByteAligned(usize::MAX).to_string(),
+            format!("2^{}", usize::MAX as u128 * 8)
+        );
+        assert_eq!(
+            Natural::from(u64::MAX)
+                .checked_add(&Natural::from(2u8))
+                .unwrap()
+                .to_string(),
+            "18446744073709551617"
+        );
+    }
 }
@@ -1,61 +1,176 @@
 #![allow(clippy::unit_arg)]
 
-use std::ops::Range;
+use std::{collections::BTreeSet, fmt, ops::Range};
 
 use chumsky::prelude::*;
-use num_bigint::BigUint;
-use num_traits::Num;
 
 use crate::{
     ast::{
-        path_span, BaseExpr, Expr, ExprVariant, Label, Labels, Reference, Scope, ScopeError,
-        Symbol, UnresolvedPath,
+        path_span, BaseExpr, Expr, ExprVariant, Label, Labels, NaturalLiteral, Reference, Scope,
+        ScopeError, Symbol, UnresolvedPath,
     },
+    cst::TokenKind,
+    cycles::cycles,
+    intern::Name,
     natural::Natural,
+    prelude::prelude,
+    source::FileId,
 };
 
-// TODO: Ref, Deref, extended labels & text expressions
+/// Max number of bits of a power literal that isn't a power of two.
+const MAX_POWER_LITERAL_BITS: u64 = 1 << 16;
+
+/// Parses a program, reporting references that can't be resolved
+/// against the [prelude](crate::prelude::prelude).
 pub fn parser() -> impl Parser<char, Scope, Error = Error> {
-    scope(recursive(|expr| {
-        labelled(reference(choice((
-            sexpr(expr)
-                .map_with_span(|scope, span| BaseExpr::variant(span, ExprVariant::SExpr(scope))),
-            natural().map_with_span(|natural, span| {
-                BaseExpr::variant(span, ExprVariant::Natural(natural))
-            }),
-            symbol().map_with_span(|symbol, span| {
-                BaseExpr::variant(
-                    span.clone(),
-                    ExprVariant::Reference(Reference::unresolved(Symbol::new(span, symbol), [])),
-                )
-            }),
-        ))))
-    }))
-    .then_ignore(
-        any()
-            .ignored()
-            .repeated()
-            .validate(|trailing_garbage, span, emit| {
-                if !trailing_garbage.is_empty() {
-                    emit(Error::trailing_garbage(span))
-                }
+    root(true)
+}
+
+/// Parses a program that can still be resolved against other outer
+/// scopes, leaving references that can't be resolved against the
+/// [prelude](crate::prelude::prelude) unresolved.
+pub fn open_parser() -> impl Parser<char, Scope, Error = Error> {
+    root(false)
+}
+
+/// Parses the expressions of a program, without resolving them, so
+/// other scopes (like [imports](crate::module)) can be added first.
+pub(crate) fn module() -> impl Parser<char, Vec<Expr>, Error = Error> {
+    top_level_exprs().then_ignore(trailing_garbage())
+}
+
+/// Builds the root scope of a program and resolves it against the
+/// [prelude](crate::prelude::prelude). When the program is closed,
+/// also reports references that can't be resolved and cycles in
+/// `file` that can't be evaluated.
+pub(crate) fn resolve_root(
+    exprs: Vec<Expr>,
+    file: FileId,
+    close: bool,
+    emit: &mut dyn FnMut(Error),
+) -> Scope {
+    let mut scope = Scope::try_from_exprs_with_emit(exprs, &mut |err| emit(Error::from(err)));
+    let prelude = prelude();
+    scope.resolve_in(&prelude, &mut |err| emit(Error::from(err)));
+    if close {
+        scope.close(&prelude, &mut |err| emit(Error::from(err)));
+        // Cycles within imported files were reported when loading them
+        for cycle in cycles(&scope)
+            .iter()
+            .filter(|cycle| !cycle.guarded && cycle.file == file)
+        {
+            let (span, other_spans) = cycle.references.split_first().unwrap();
+            emit(Error::unguarded_cycle(span.clone(), other_spans))
+        }
+    }
+
+    scope
+}
+
+fn root(close: bool) -> impl Parser<char, Scope, Error = Error> {
+    top_level_exprs()
+        .validate(move |exprs, _span, emit| resolve_root(exprs, FileId::default(), close, emit))
+        .then_ignore(trailing_garbage())
+}
+
+fn expr() -> impl Parser<char, Result<Expr, ()>, Error = Error> + Clone {
+    recursive(|expr| {
+        labelled(choice((
+            value_ref(),
+            recursive(|base| {
+                choice((
+                    deref(base),
+                    reference(choice((
+                        sexpr(expr).map_with_span(|scope, span| {
+                            Ok(BaseExpr::variant(span, ExprVariant::SExpr(scope)))
+                        }),
+                        natural().map_with_span(|natural, span| {
+                            Ok(BaseExpr::variant(span, ExprVariant::Natural(natural)))
+                        }),
+                        text().map_with_span(|text, span| {
+                            Ok(BaseExpr::variant(span, ExprVariant::Text(text)))
+                        }),
+                        symbol_reference(),
+                    ))),
+                ))
             }),
-    )
+        )))
+    })
+}
+
+/// Splits a program into the tokens of a [Cst](crate::cst::Cst), using
+/// the same parsers as the expressions so that the tokens line up with
+/// their spans. Anything that can't be parsed still makes a token.
+pub(crate) fn tokens() -> impl Parser<char, Vec<(TokenKind, Range<usize>)>, Error = Error> {
+    let skips = just('\\').repeated();
+    choice((
+        whitespace()
+            .then(whitespace().repeated())
+            .to(TokenKind::Whitespace),
+        line_comment().to(TokenKind::LineComment),
+        block_comment().to(TokenKind::BlockComment),
+        just('(').to(TokenKind::LeftParen),
+        just(')').to(TokenKind::RightParen),
+        just(':').to(TokenKind::Colon),
+        just('@').to(TokenKind::At),
+        just('$').to(TokenKind::Dollar),
+        natural().to(TokenKind::Natural),
+        text().to(TokenKind::Text),
+        skips.then(extended_symbol()).to(TokenKind::QuotedSymbol),
+        skips.then(symbol()).to(TokenKind::Symbol),
+        skips.at_least(1).to(TokenKind::Symbol),
+    ))
+    .map_with_span(|kind, span| (kind, span))
+    .repeated()
+    .then_ignore(end())
+}
+
+/// Parses the expressions of a program, skipping past any unmatched
+/// `)` so that the expressions after it are still parsed.
+fn top_level_exprs() -> impl Parser<char, Vec<Expr>, Error = Error> + Clone {
+    let exprs = exprs_with_failure(expr(), |_exprs, _index| false)
+        .then(just(')').map_with_span(|_, span| span).rewind().or_not())
+        .validate(|((exprs, failed), unmatched), _span, emit| {
+            // An expression that failed right before the `)` already
+            // reported it
+            if let Some(span) = unmatched.filter(|_| !failed) {
+                emit(Error::unexpected_char(span, ')'))
+            }
+
+            exprs
+        });
+
+    exprs
+        .clone()
+        .chain(just(')').ignore_then(exprs).repeated().flatten())
+}
+
+fn trailing_garbage() -> impl Parser<char, (), Error = Error> + Clone {
+    any()
+        .ignored()
+        .repeated()
+        .validate(|trailing_garbage, span, emit| {
+            if !trailing_garbage.is_empty() {
+                emit(Error::trailing_garbage(span))
+            }
+        })
 }
 
 fn labelled(
     expr: impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone,
 ) -> impl Parser<char, Result<Expr, ()>, Error = Error> + Clone {
     let labels_with_expr = label()
-        .separated_by(text::whitespace())
+        .separated_by(trivia())
+        .allow_trailing()
         .at_least(1)
         .collect::<Result<Box<Labels>, ()>>()
-        .then_ignore(text::whitespace().at_least(1).or_not())
         .then(expr.clone().or_not())
         .validate(|(labels, expr), span, emit| match labels {
             Ok(labels) => match expr {
                 Some(expr) => expr.map(|expr| expr.with_labels(labels)),
-                None => Err(emit(Error::unexpected_end(span.end))),
+                None => Err(emit(
+                    Error::unexpected_end(span.end).expecting(Expected::Expr),
+                )),
             },
             Err(()) => match expr {
                 Some(expr) => expr.map(|expr| expr.with_labels(Box::new([]))),
@@ -74,25 +189,57 @@ fn label() -> impl Parser<char, Result<Label, ()>, Error = Error> + Clone {
     just(':')
         .ignore_then(symbol().map(Ok).or_else(|err| Ok(Err(err))))
         .validate(|symbol, span, emit| match symbol {
-            Ok(symbol) => Ok(Label::new(span, symbol)),
+            Ok(symbol) => symbol.map(|(name, doc)| Label { span, name, doc }),
             Err(err) => Err(emit(err)),
         })
         .labelled(ErrorLabel::Label)
 }
 
 fn reference(
-    expr: impl Parser<char, BaseExpr, Error = Error> + Clone,
+    expr: impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone,
 ) -> impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone {
     expr.then(path())
         .validate(|(expr, path), _span, emit| {
-            path.and_then(|path| {
-                expr.take_from_path(path)
-                    .map_err(|(path, depth)| emit(Error::invalid_path(path_span(&path[depth..]))))
-            })
+            let (expr, path) = (expr?, path?);
+            expr.take_from_path(path)
+                .map_err(|(path, depth)| emit(Error::invalid_path(path_span(&path[depth..]))))
         })
         .labelled(ErrorLabel::Reference)
 }
 
+fn value_ref() -> impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone {
+    just('$')
+        .ignore_then(
+            reference(symbol_reference())
+                .map(Ok)
+                .or_else(|err| Ok(Err(err))),
+        )
+        .validate(|base, span, emit| match base {
+            Ok(base) => base.map(|base| match base.variant {
+                ExprVariant::Reference(reference) => {
+                    BaseExpr::variant(span, ExprVariant::ValueRef(reference))
+                }
+                _ => unreachable!(),
+            }),
+            Err(err) => Err(emit(err)),
+        })
+        .labelled(ErrorLabel::ValueRef)
+}
+
+fn deref(
+    base: impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone,
+) -> impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone {
+    just('@')
+        .ignore_then(base.map(Ok).or_else(|err| Ok(Err(err))))
+        .validate(|base, span, emit| match base {
+            Ok(base) => {
+                base.map(|base| BaseExpr::variant(span, ExprVariant::Deref(Box::new(base))))
+            }
+            Err(err) => Err(emit(err)),
+        })
+        .labelled(ErrorLabel::Deref)
+}
+
 fn path() -> impl Parser<char, Result<Box<UnresolvedPath>, ()>, Error = Error> + Clone {
     label().repeated().collect().labelled(ErrorLabel::Path)
 }
@@ -100,115 +247,460 @@ fn path() -> impl Parser<char, Result<Box<UnresolvedPath>, ()>, Error = Error> +
 fn sexpr(
     expr: impl Parser<char, Result<Expr, ()>, Error = Error> + Clone,
 ) -> impl Parser<char, Scope, Error = Error> + Clone {
-    scope(expr)
-        .delimited_by(
-            just('('),
-            just(')')
-                .map(|_| Ok(()))
-                .or_else(|err| Ok(Err(err)))
-                .validate(|result, _span, emit| result.map_err(emit)),
-        )
+    // A missing `)` at the end of the program doesn't hide anything,
+    // but anything else skips to the matching `)` to keep parsing after it
+    let end = end()
+        .map_err(unexpected)
+        .validate(|_, span: Range<usize>, emit| {
+            emit(Error::unexpected_end(span.start).expecting(Expected::Char(')')))
+        });
+    scope(expr, value_context)
+        .delimited_by(just('('), just(')').ignored().or(end))
+        .recover_with(nested_delimiters('(', ')', [], |_| Scope::default()))
         .labelled(ErrorLabel::SExpr)
 }
 
+/// Value expressions can only be used in product expressions and the
+/// base of map expressions.
+fn value_context(exprs: &[Expr], index: usize) -> bool {
+    match exprs.first() {
+        Some(head) if head.base.is_symbol("*") => index > 0,
+        Some(head) if head.base.is_symbol("^") => index == 1,
+        _ => false,
+    }
+}
+
 fn scope(
     expr: impl Parser<char, Result<Expr, ()>, Error = Error> + Clone,
+    value_context: fn(&[Expr], usize) -> bool,
 ) -> impl Parser<char, Scope, Error = Error> + Clone {
-    expr.separated_by(text::whitespace().at_least(1))
-        .flatten()
-        .validate(|exprs, _span, emit| {
-            Scope::try_from_exprs_with_emit(exprs, &mut |err| {
-                emit(match err {
-                    ScopeError::DuplicateLabel(span, other_span) => {
-                        Error::duplicate_label(span, other_span)
+    exprs(expr, value_context).validate(|exprs, _span, emit| {
+        Scope::try_from_exprs_with_emit(exprs, &mut |err| emit(Error::from(err)))
+    })
+}
+
+fn exprs(
+    expr: impl Parser<char, Result<Expr, ()>, Error = Error> + Clone,
+    value_context: fn(&[Expr], usize) -> bool,
+) -> impl Parser<char, Vec<Expr>, Error = Error> + Clone {
+    exprs_with_failure(expr, value_context).map(|(exprs, _)| exprs)
+}
+
+/// Parses expressions like [exprs], also returning whether the last one
+/// failed to parse right where the expressions end.
+fn exprs_with_failure(
+    expr: impl Parser<char, Result<Expr, ()>, Error = Error> + Clone,
+    value_context: fn(&[Expr], usize) -> bool,
+) -> impl Parser<char, (Vec<Expr>, bool), Error = Error> + Clone {
+    trivia()
+        .ignore_then(
+            expr.map_with_span(|expr, span: Range<usize>| (expr, span.end))
+                .separated_by(trivium().repeated().at_least(1))
+                .allow_trailing(),
+        )
+        .then(empty().map_with_span(|(), span: Range<usize>| span.start))
+        .then_ignore(trivia())
+        .validate(move |(exprs, end), _span, emit| {
+            let failed = matches!(exprs.last(), Some((Err(()), failed_at)) if *failed_at == end);
+            let exprs = exprs
+                .into_iter()
+                .flat_map(|(expr, _)| expr)
+                .collect::<Vec<_>>();
+            for (index, expr) in exprs.iter().enumerate() {
+                if matches!(expr.base.variant, ExprVariant::ValueRef(_))
+                    && !value_context(&exprs, index)
+                {
+                    emit(Error::invalid_value_ref(expr.base.span.clone()))
+                }
+            }
+
+            (exprs, failed)
+        })
+}
+
+fn trivia() -> impl Parser<char, (), Error = Error> + Clone {
+    trivium().repeated().ignored()
+}
+
+/// Parses whitespace or a comment, which are ignored between
+/// expressions.
+fn trivium() -> impl Parser<char, (), Error = Error> + Clone {
+    choice((whitespace(), line_comment(), block_comment()))
+        // Comments can go anywhere whitespace can, so they aren't worth
+        // mentioning as expected
+        .map_err(unexpected)
+}
+
+fn whitespace() -> impl Parser<char, (), Error = Error> + Clone {
+    filter(|c: &char| c.is_whitespace()).ignored()
+}
+
+/// Parses a comment from `;` to the end of the line.
+fn line_comment() -> impl Parser<char, (), Error = Error> + Clone {
+    just(';')
+        .then(filter(|c: &char| *c != '\n').repeated())
+        .ignored()
+}
+
+/// Parses a comment wrapped in `#|` and `|#`, which can be nested.
+fn block_comment() -> impl Parser<char, (), Error = Error> + Clone {
+    recursive(|block_comment| {
+        just("#|")
+            .ignore_then(block_comment.or(just("|#").not().ignored()).repeated())
+            .then_ignore(
+                just("|#")
+                    .ignored()
+                    .or(end().validate(|_, span: Range<usize>, emit| {
+                        emit(Error::unexpected_end(span.start).expecting(Expected::CommentEnd))
+                    })),
+            )
+            .ignored()
+    })
+}
+
+/// Removes the expected tokens from an error.
+fn unexpected(err: Error) -> Error {
+    Error {
+        expected: BTreeSet::new(),
+        ..err
+    }
+}
+
+fn natural() -> impl Parser<char, NaturalLiteral, Error = Error> + Clone {
+    let prefixed_digit = |radix| move |c: &char| *c == '_' || c.is_digit(radix);
+    let prefixed = |prefix, radix| {
+        just(prefix)
+            .then(digits(radix, prefixed_digit(radix)))
+            .map(|(prefix, (value, digits))| (value, format!("{prefix}{digits}")))
+    };
+
+    let literal = choice((
+        prefixed("0b", 2),
+        prefixed("0o", 8),
+        prefixed("0x", 16),
+        digits(10, |c: &char| matches!(c, '1'..='9')),
+        just('0').to((Natural::from(0u8), "0".to_owned())),
+    ));
+
+    literal
+        .clone()
+        .then(just('^').ignore_then(literal).or_not())
+        .validate(|((base, source), exponent), span, emit| match exponent {
+            Some((exponent, exponent_source)) => NaturalLiteral {
+                value: power(&base, &exponent).unwrap_or_else(|| {
+                    emit(Error::natural_overflow(span));
+                    Natural::from(0u8)
+                }),
+                source: Some(format!("{source}^{exponent_source}").into()),
+            },
+            None => NaturalLiteral {
+                value: base,
+                source: Some(source.into()),
+            },
+        })
+        .labelled(ErrorLabel::Natural)
+}
+
+/// Raises a power literal, returning [None] if it's too large.
+///
+/// Powers of two only store their number of bits, but any other power
+/// is computed in full while parsing, so it's limited to
+/// [MAX_POWER_LITERAL_BITS].
+fn power(base: &Natural, exponent: &Natural) -> Option<Natural> {
+    if let (None, Some(ilog2)) = (base.exact_log2(), base.checked_ilog2()) {
+        let bits = (ilog2 + 1).checked_mul(exponent.to_usize()? as u64)?;
+        if bits > MAX_POWER_LITERAL_BITS {
+            return None;
+        }
+    }
+
+    base.checked_pow(exponent)
+}
+
+/// Parses digits with optional underscores as separators, which must
+/// be placed between two digits, along with the way they were written.
+fn digits(
+    radix: u32,
+    first: impl Fn(&char) -> bool + Clone,
+) -> impl Parser<char, (Natural, String), Error = Error> + Clone {
+    filter(first)
+        .map_err(|err: Error| err.expecting(Expected::Digit))
+        .chain(filter(move |c: &char| *c == '_' || c.is_digit(radix)).repeated())
+        .validate(move |chars: Vec<char>, span: Range<usize>, emit| {
+            let mut digits = Vec::with_capacity(chars.len());
+            let mut separators = None;
+            for (index, c) in chars.iter().enumerate() {
+                match c.to_digit(radix) {
+                    Some(digit) => {
+                        if let Some((start, end)) = separators.take() {
+                            if start == 0 || end - start > 1 {
+                                emit(Error::invalid_separator(
+                                    span.start + start..span.start + end,
+                                ))
+                            }
+                        }
+
+                        digits.push(digit as u8);
                     }
-                    ScopeError::InvalidPath(span) => Error::invalid_path(span),
-                })
-            })
+                    None => {
+                        let (start, _) = separators.unwrap_or((index, index));
+                        separators = Some((start, index + 1));
+                    }
+                }
+            }
+
+            if let Some((start, end)) = separators {
+                emit(Error::invalid_separator(
+                    span.start + start..span.start + end,
+                ))
+            }
+
+            let value = Natural::from_digits(radix, &digits).expect("digits are in the radix");
+            (value, chars.into_iter().collect())
         })
-        .padded()
 }
 
-fn natural() -> impl Parser<char, Natural, Error = Error> + Clone {
-    // TODO: Support underscore for separator
-    // TODO: Manually build natural from digits to avoid overhead of
-    // converting to string + parse
+/// Parses a symbol, where each leading `\` skips the innermost label
+/// matching it to refer to a label shadowed in a parent scope.
+fn symbol_reference() -> impl Parser<char, Result<BaseExpr, ()>, Error = Error> + Clone {
+    just('\\')
+        .repeated()
+        .then(
+            symbol()
+                .map_with_span(|symbol, span| symbol.map(|(name, doc)| Symbol { span, name, doc })),
+        )
+        .map_with_span(|(skips, symbol), span| {
+            let reference = Reference::unresolved_skipping(symbol?, [], skips.len());
+            Ok(BaseExpr::variant(span, ExprVariant::Reference(reference)))
+        })
+}
+
+/// Parses a symbol, which is [Err] if it was quoted but never
+/// terminated, so that it isn't resolved on top of being reported.
+fn symbol() -> impl Parser<char, Result<(Name, Option<String>), ()>, Error = Error> + Clone {
     choice((
-        just('0').ignore_then(choice((
-            just("b")
-                .ignore_then(text::int(2).map(|s: String| BigUint::from_str_radix(&s, 2).unwrap())),
-            just("o")
-                .ignore_then(text::int(8).map(|s: String| BigUint::from_str_radix(&s, 8).unwrap())),
-            just("x").ignore_then(
-                text::int(16).map(|s: String| BigUint::from_str_radix(&s, 16).unwrap()),
-            ),
-        ))),
-        text::int(10).map(|s: String| BigUint::from_str_radix(&s, 10).unwrap()),
+        extended_symbol(),
+        filter(|c| *c != '\\' && symbol_char(c))
+            .map_err(|err: Error| err.expecting(Expected::Symbol))
+            .chain(filter(symbol_char).repeated())
+            .collect::<String>()
+            .map(|name| Ok((Name::new(&name), None))),
     ))
-    .map(Natural::from)
-    .labelled(ErrorLabel::Natural)
+    .labelled(ErrorLabel::Symbol)
 }
 
-fn symbol() -> impl Parser<char, String, Error = Error> + Clone {
-    filter(symbol_char)
-        .repeated()
-        .at_least(1)
-        .collect()
-        .labelled(ErrorLabel::Symbol)
+/// Parses a quoted symbol. Anything following whitespace is treated
+/// as documentation.
+fn extended_symbol() -> impl Parser<char, Result<(Name, Option<String>), ()>, Error = Error> + Clone
+{
+    quoted('\'').validate(|(text, terminated), span, emit| {
+        if !terminated {
+            return Err(());
+        }
+
+        let (name, doc) = match text.split_once(char::is_whitespace) {
+            Some((name, doc)) => (name, Some(doc.trim()).filter(|doc| !doc.is_empty())),
+            None => (text.as_str(), None),
+        };
+
+        if name.is_empty() {
+            emit(Error::empty_symbol(span))
+        }
+
+        Ok((Name::new(name), doc.map(str::to_owned)))
+    })
+}
+
+fn text() -> impl Parser<char, String, Error = Error> + Clone {
+    quoted('"').map(|(text, _)| text).labelled(ErrorLabel::Text)
+}
+
+/// Parses text wrapped in quotes, which only terminates after an odd
+/// run of quotes. Quotes are escaped by doubling them up.
+///
+/// A missing closing quote is reported, and the text up to the end of
+/// the input is returned along with `false`.
+fn quoted(quote: char) -> impl Parser<char, (String, bool), Error = Error> + Clone {
+    just(quote)
+        .ignore_then(
+            filter(move |c: &char| *c != quote)
+                .or(just(quote).ignore_then(just(quote)))
+                .repeated()
+                .collect(),
+        )
+        .then(
+            just(quote)
+                .map(|_| Ok(()))
+                .or_else(|err| Ok(Err(err)))
+                .validate(|result, _span, emit| result.map_err(emit).is_ok()),
+        )
 }
 
-fn symbol_char(c: &char) -> bool {
+pub(crate) fn symbol_char(c: &char) -> bool {
     match c {
-        ':' | '(' | ')' => false,
+        ':' | '(' | ')' | '@' | '$' | '\'' | '"' | ';' => false,
         c => !c.is_whitespace(),
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Error {
+    /// The file [span](Self::span) is in.
+    pub file: FileId,
+
     pub span: Range<usize>,
     pub variant: ErrorVariant,
     pub trace: Vec<ErrorLabel>,
+
+    /// What would have been accepted instead.
+    expected: BTreeSet<Expected>,
+}
+
+/// Compares what was [expected](Error::expected), so that the ways to
+/// start an expression don't have to be listed one by one.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.file == other.file
+            && self.span == other.span
+            && self.variant == other.variant
+            && self.trace == other.trace
+            && self.expected() == other.expected()
+    }
 }
 
+impl Eq for Error {}
+
 impl Error {
     pub fn unexpected_char(span: Range<usize>, found: char) -> Self {
         Self {
+            file: FileId::default(),
             variant: ErrorVariant::UnexpectedChar(Some(found)),
             span,
             trace: Vec::new(),
+            expected: BTreeSet::new(),
         }
     }
 
     pub fn unexpected_end(pos: usize) -> Self {
         Self {
+            file: FileId::default(),
             variant: ErrorVariant::UnexpectedChar(None),
             span: pos..pos,
             trace: Vec::new(),
+            expected: BTreeSet::new(),
         }
     }
 
     pub fn duplicate_label(span: Range<usize>, other_span: Range<usize>) -> Self {
         Self {
+            file: FileId::default(),
             variant: ErrorVariant::DuplicateLabel(other_span),
             span,
             trace: Vec::new(),
+            expected: BTreeSet::new(),
         }
     }
 
     pub fn invalid_path(span: Range<usize>) -> Self {
         Self {
+            file: FileId::default(),
             variant: ErrorVariant::InvalidPath,
             span,
             trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn empty_symbol(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::EmptySymbol,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn invalid_value_ref(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::InvalidValueRef,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn invalid_separator(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::InvalidSeparator,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn natural_overflow(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::NaturalOverflow,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn unresolved_symbol(span: Range<usize>, suggestion: Option<String>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::UnresolvedSymbol(suggestion),
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn unguarded_cycle(
+        span: Range<usize>,
+        other_spans: impl Into<Box<[Range<usize>]>>,
+    ) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::UnguardedCycle(other_spans.into()),
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn import_not_found(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::ImportNotFound,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    pub fn import_cycle(span: Range<usize>) -> Self {
+        Self {
+            file: FileId::default(),
+            variant: ErrorVariant::ImportCycle,
+            span,
+            trace: Vec::new(),
+            expected: BTreeSet::new(),
         }
     }
 
     pub fn trailing_garbage(span: Range<usize>) -> Self {
         Self {
+            file: FileId::default(),
             variant: ErrorVariant::TrailingGarbage,
             span,
             trace: Vec::new(),
+            expected: BTreeSet::new(),
         }
     }
 
@@ -216,6 +708,79 @@ impl Error {
         self.trace.push(label);
         self
     }
+
+    pub fn in_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        self
+    }
+
+    pub fn expecting(mut self, expected: Expected) -> Self {
+        self.expected.insert(expected);
+        self
+    }
+
+    /// What would have been accepted instead, if this is an
+    /// [UnexpectedChar](ErrorVariant::UnexpectedChar). Every way to
+    /// start an expression is collapsed into [Expected::Expr].
+    pub fn expected(&self) -> Vec<Expected> {
+        let is_expr = EXPR_STARTS
+            .iter()
+            .all(|expected| self.expected.contains(expected));
+
+        let mut expected = self
+            .expected
+            .iter()
+            .copied()
+            .filter(|expected| !(is_expr && expected.starts_expr()))
+            .collect::<Vec<_>>();
+
+        if is_expr {
+            expected.push(Expected::Expr);
+            expected.sort();
+        }
+
+        expected
+    }
+}
+
+/// Describes what was expected when there's anything to describe, like
+/// "expected `)` or an expression, found `]`".
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ErrorVariant::UnexpectedChar(found) = self.variant else {
+            return self.variant.fmt(f);
+        };
+
+        let expected = self.expected();
+        let Some((last, rest)) = expected.split_last() else {
+            return self.variant.fmt(f);
+        };
+
+        write!(f, "expected ")?;
+        for (index, expected) in rest.iter().enumerate() {
+            let separator = if index + 1 < rest.len() { ", " } else { " or " };
+            write!(f, "{expected}{separator}")?;
+        }
+
+        match found {
+            Some(found) => write!(f, "{last}, found `{found}`"),
+            None => write!(f, "{last}, found the end of input"),
+        }
+    }
+}
+
+impl From<ScopeError> for Error {
+    fn from(err: ScopeError) -> Self {
+        match err {
+            ScopeError::DuplicateLabel(span, other_span) => {
+                Error::duplicate_label(span, other_span)
+            }
+            ScopeError::InvalidPath(span) => Error::invalid_path(span),
+            ScopeError::UnresolvedReference(span, suggestion) => {
+                Error::unresolved_symbol(span, suggestion)
+            }
+        }
+    }
 }
 
 impl chumsky::Error<char> for Error {
@@ -225,14 +790,19 @@ impl chumsky::Error<char> for Error {
 
     fn expected_input_found<Iter: IntoIterator<Item = Option<char>>>(
         span: Self::Span,
-        _expected: Iter,
+        expected: Iter,
         found: Option<char>,
     ) -> Self {
-        if let Some(found) = found {
+        let err = if let Some(found) = found {
             Self::unexpected_char(span, found)
         } else {
             debug_assert_eq!(span.start, span.end);
             Self::unexpected_end(span.end)
+        };
+
+        Self {
+            expected: expected.into_iter().map(Expected::from).collect(),
+            ..err
         }
     }
 
@@ -240,7 +810,10 @@ impl chumsky::Error<char> for Error {
         Error::with_label(self, label)
     }
 
-    fn merge(self, _other: Self) -> Self {
+    /// Merges errors from alternatives that failed at the same place,
+    /// so that each alternative is expected.
+    fn merge(mut self, mut other: Self) -> Self {
+        self.expected.append(&mut other.expected);
         self
     }
 }
@@ -248,18 +821,152 @@ impl chumsky::Error<char> for Error {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ErrorVariant {
     UnexpectedChar(Option<char>),
+    EmptySymbol,
     DuplicateLabel(Range<usize>),
     InvalidPath,
+    InvalidValueRef,
+    InvalidSeparator,
+    NaturalOverflow,
+    /// A symbol that isn't labelled in scope, along with a suggested
+    /// label.
+    UnresolvedSymbol(Option<String>),
+    /// A reference that's part of a cycle that never bottoms out, along
+    /// with the other references in the cycle.
+    UnguardedCycle(Box<[Range<usize>]>),
+    /// An imported file that couldn't be found or read.
+    ImportNotFound,
+    /// An imported file that's still being loaded, because it imports
+    /// the file importing it.
+    ImportCycle,
     TrailingGarbage,
 }
 
+impl ErrorVariant {
+    /// A code identifying the kind of error, like `E0004`. Codes are
+    /// never reused or renumbered, so they can be searched for.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorVariant::UnexpectedChar(_) => "E0001",
+            ErrorVariant::EmptySymbol => "E0002",
+            ErrorVariant::DuplicateLabel(_) => "E0003",
+            ErrorVariant::InvalidPath => "E0004",
+            ErrorVariant::InvalidValueRef => "E0005",
+            ErrorVariant::InvalidSeparator => "E0006",
+            ErrorVariant::NaturalOverflow => "E0007",
+            ErrorVariant::UnresolvedSymbol(_) => "E0008",
+            ErrorVariant::UnguardedCycle(_) => "E0009",
+            ErrorVariant::ImportNotFound => "E0010",
+            ErrorVariant::ImportCycle => "E0011",
+            ErrorVariant::TrailingGarbage => "E0012",
+        }
+    }
+}
+
+impl fmt::Display for ErrorVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorVariant::UnexpectedChar(Some(c)) => write!(f, "unexpected character `{c}`"),
+            ErrorVariant::UnexpectedChar(None) => write!(f, "unexpected end of input"),
+            ErrorVariant::EmptySymbol => write!(f, "empty symbol"),
+            ErrorVariant::DuplicateLabel(_) => write!(f, "duplicate label"),
+            ErrorVariant::InvalidPath => write!(f, "invalid path"),
+            ErrorVariant::InvalidValueRef => {
+                write!(f, "value reference outside of a product or map")
+            }
+            ErrorVariant::InvalidSeparator => write!(f, "invalid digit separator"),
+            ErrorVariant::NaturalOverflow => write!(f, "natural is too large"),
+            ErrorVariant::UnresolvedSymbol(_) => write!(f, "unresolved symbol"),
+            ErrorVariant::UnguardedCycle(_) => write!(f, "unguarded cycle"),
+            ErrorVariant::ImportNotFound => write!(f, "imported file not found"),
+            ErrorVariant::ImportCycle => write!(f, "import cycle"),
+            ErrorVariant::TrailingGarbage => write!(f, "unexpected input after the program"),
+        }
+    }
+}
+
+/// Something that would have been accepted where an
+/// [UnexpectedChar](ErrorVariant::UnexpectedChar) was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Expected {
+    Char(char),
+    Digit,
+    /// A character of a symbol or label name.
+    Symbol,
+    /// Any of the ways to start an expression.
+    Expr,
+    /// The `|#` closing a block comment.
+    CommentEnd,
+    End,
+}
+
+/// The least that has to be expected to be expecting an expression.
+const EXPR_STARTS: [Expected; 4] = [
+    Expected::Char('('),
+    Expected::Char('"'),
+    Expected::Digit,
+    Expected::Symbol,
+];
+
+impl Expected {
+    fn starts_expr(self) -> bool {
+        match self {
+            Expected::Char(c) => matches!(c, '(' | '"' | '\'' | '@' | '$' | ':' | '\\'),
+            Expected::Digit | Expected::Symbol | Expected::Expr => true,
+            Expected::CommentEnd | Expected::End => false,
+        }
+    }
+}
+
+impl From<Option<char>> for Expected {
+    fn from(c: Option<char>) -> Self {
+        match c {
+            Some(c) if c.is_ascii_digit() => Expected::Digit,
+            Some(c) => Expected::Char(c),
+            None => Expected::End,
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Char(c) => write!(f, "`{c}`"),
+            Expected::Digit => write!(f, "a digit"),
+            Expected::Symbol => write!(f, "a symbol"),
+            Expected::Expr => write!(f, "an expression"),
+            Expected::CommentEnd => write!(f, "`|#`"),
+            Expected::End => write!(f, "the end of input"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ErrorLabel {
     Natural,
+    Text,
     Symbol,
     Label,
     LabelledExpr,
     Path,
     Reference,
+    ValueRef,
+    Deref,
     SExpr,
 }
+
+impl fmt::Display for ErrorLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorLabel::Natural => "natural",
+            ErrorLabel::Text => "text",
+            ErrorLabel::Symbol => "symbol",
+            ErrorLabel::Label => "label",
+            ErrorLabel::LabelledExpr => "labelled expression",
+            ErrorLabel::Path => "path",
+            ErrorLabel::Reference => "reference",
+            ErrorLabel::ValueRef => "value reference",
+            ErrorLabel::Deref => "dereference",
+            ErrorLabel::SExpr => "s-expression",
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    ast::{Expr, ExprVariant, Label, Scope},
+    text::Encoding,
+};
+
+/// A symbol that's predefined by the language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Exponentiate,
+    Logarithm,
+    Root,
+    Assert,
+    Union,
+    Intersection,
+    Complement,
+    SymmetricDifference,
+    Interval,
+    ZeroOrOne,
+    Top,
+    Codepoint,
+    Grapheme,
+    Encoding(Encoding),
+}
+
+impl Builtin {
+    /// Every builtin, in the order they're defined in the [prelude].
+    pub const ALL: [Builtin; 22] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::Exponentiate,
+        Self::Logarithm,
+        Self::Root,
+        Self::Assert,
+        Self::Union,
+        Self::Intersection,
+        Self::Complement,
+        Self::SymmetricDifference,
+        Self::Interval,
+        Self::ZeroOrOne,
+        Self::Top,
+        Self::Codepoint,
+        Self::Grapheme,
+        Self::Encoding(Encoding::Ascii7),
+        Self::Encoding(Encoding::Ascii8),
+        Self::Encoding(Encoding::Utf8),
+        Self::Encoding(Encoding::Utf16),
+        Self::Encoding(Encoding::Utf32),
+    ];
+
+    /// The symbols bound to this builtin.
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            Self::Add => &["+"],
+            Self::Subtract => &["-"],
+            Self::Multiply => &["*"],
+            Self::Divide => &["/"],
+            Self::Exponentiate => &["^"],
+            Self::Logarithm => &["log"],
+            Self::Root => &["root"],
+            Self::Assert => &["="],
+            Self::Union => &["|"],
+            Self::Intersection => &["&"],
+            Self::Complement => &["!"],
+            Self::SymmetricDifference => &["~"],
+            Self::Interval => &[".."],
+            Self::ZeroOrOne => &["?"],
+            Self::Top => &["_"],
+            Self::Codepoint => &["codepoint"],
+            Self::Grapheme => &["grapheme"],
+            Self::Encoding(Encoding::Ascii7) => &["ascii-7"],
+            Self::Encoding(Encoding::Ascii8) => &["ascii", "ascii-8"],
+            Self::Encoding(Encoding::Utf8) => &["utf-8"],
+            Self::Encoding(Encoding::Utf16) => &["utf-16"],
+            Self::Encoding(Encoding::Utf32) => &["utf-32"],
+        }
+    }
+
+    /// The offset of this builtin relative to a scope resolved
+    /// against the [prelude].
+    pub fn offset(self) -> isize {
+        let index = Self::ALL
+            .iter()
+            .position(|builtin| *builtin == self)
+            .expect("every builtin is in the prelude");
+
+        index as isize - Self::ALL.len() as isize
+    }
+}
+
+/// The outer scope that every program is resolved against, which
+/// labels each [Builtin] with its symbols.
+pub fn prelude() -> Scope {
+    Scope::try_from_exprs(Builtin::ALL.map(|builtin| {
+        let labels = builtin
+            .names()
+            .iter()
+            .map(|name| Label::new(0..0, *name))
+            .collect::<Vec<_>>();
+
+        Expr::variant(labels, 0..0, ExprVariant::Builtin(builtin))
+    }))
+    .expect("builtins have unique names")
+}
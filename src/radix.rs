@@ -0,0 +1,349 @@
+use std::mem;
+
+/// A map from strings to values that stores shared key prefixes once.
+///
+/// Entries are iterated in lexicographic order of their keys.
+#[derive(Debug, Clone)]
+pub struct RadixTree<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    value: Option<V>,
+
+    /// Sorted by the first byte of their labels, which is unique.
+    edges: Vec<Edge<V>>,
+}
+
+#[derive(Debug, Clone)]
+struct Edge<V> {
+    label: Box<[u8]>,
+    node: Node<V>,
+}
+
+impl<V> RadixTree<V> {
+    pub const fn new() -> Self {
+        Self {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        let mut node = &self.root;
+        let mut key = key.as_bytes();
+        while let Some(&first) = key.first() {
+            let edge = node.edge(first)?;
+            key = key.strip_prefix(&*edge.label)?;
+            node = &edge.node;
+        }
+
+        node.value.as_ref()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Gets the value of `key`, inserting the result of `f` if it
+    /// doesn't have one yet.
+    pub fn get_or_insert_with(&mut self, key: &str, f: impl FnOnce() -> V) -> &mut V {
+        let value = self.root.slot(key.as_bytes());
+        if value.is_none() {
+            self.len += 1;
+        }
+
+        value.get_or_insert_with(f)
+    }
+
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let old = self.root.slot(key.as_bytes()).replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let value = self.root.remove(key.as_bytes())?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            stack: vec![(0, &[][..], &self.root)],
+            key: Vec::new(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = String> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> IntoIterator for RadixTree<V> {
+    type Item = (String, V);
+
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            stack: vec![(0, Box::default(), self.root)],
+            key: Vec::new(),
+        }
+    }
+}
+
+impl<V> Node<V> {
+    const fn new() -> Self {
+        Self {
+            value: None,
+            edges: Vec::new(),
+        }
+    }
+
+    fn edge(&self, first: u8) -> Option<&Edge<V>> {
+        let index = self.edge_index(first).ok()?;
+        Some(&self.edges[index])
+    }
+
+    fn edge_index(&self, first: u8) -> Result<usize, usize> {
+        self.edges
+            .binary_search_by_key(&first, |edge| edge.label[0])
+    }
+
+    /// The value of `key`, creating the nodes leading to it.
+    fn slot(&mut self, key: &[u8]) -> &mut Option<V> {
+        let Some(&first) = key.first() else {
+            return &mut self.value;
+        };
+
+        let index = match self.edge_index(first) {
+            Ok(index) => index,
+            Err(index) => {
+                self.edges.insert(
+                    index,
+                    Edge {
+                        label: key.into(),
+                        node: Node::new(),
+                    },
+                );
+
+                return &mut self.edges[index].node.value;
+            }
+        };
+
+        let edge = &mut self.edges[index];
+        let common = common_prefix_len(&edge.label, key);
+        if common < edge.label.len() {
+            // Split the edge where the key diverges from it
+            let node = mem::replace(&mut edge.node, Node::new());
+            edge.node.edges.push(Edge {
+                label: edge.label[common..].into(),
+                node,
+            });
+
+            edge.label = edge.label[..common].into();
+        }
+
+        edge.node.slot(&key[common..])
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let Some(&first) = key.first() else {
+            return self.value.take();
+        };
+
+        let index = self.edge_index(first).ok()?;
+        let edge = &mut self.edges[index];
+        let value = edge.node.remove(key.strip_prefix(&*edge.label)?)?;
+        if edge.node.value.is_none() {
+            match edge.node.edges.len() {
+                0 => {
+                    self.edges.remove(index);
+                }
+                1 => {
+                    // Merge the edge with its only child
+                    let child = edge.node.edges.pop().unwrap();
+                    edge.label = [&*edge.label, &*child.label].concat().into();
+                    edge.node = child.node;
+                }
+                _ => (),
+            }
+        }
+
+        Some(value)
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Joins the labels leading to a value into its key.
+fn key_string(key: &[u8]) -> String {
+    String::from_utf8(key.to_vec()).expect("keys are built from strings")
+}
+
+pub struct Iter<'a, V> {
+    stack: Vec<(usize, &'a [u8], &'a Node<V>)>,
+    key: Vec<u8>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, label, node)) = self.stack.pop() {
+            self.key.truncate(depth);
+            self.key.extend_from_slice(label);
+            for edge in node.edges.iter().rev() {
+                self.stack.push((self.key.len(), &edge.label, &edge.node));
+            }
+
+            if let Some(value) = &node.value {
+                return Some((key_string(&self.key), value));
+            }
+        }
+
+        None
+    }
+}
+
+pub struct IntoIter<V> {
+    stack: Vec<(usize, Box<[u8]>, Node<V>)>,
+    key: Vec<u8>,
+}
+
+impl<V> Iterator for IntoIter<V> {
+    type Item = (String, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, label, node)) = self.stack.pop() {
+            self.key.truncate(depth);
+            self.key.extend_from_slice(&label);
+            for edge in node.edges.into_iter().rev() {
+                self.stack.push((self.key.len(), edge.label, edge.node));
+            }
+
+            if let Some(value) = node.value {
+                return Some((key_string(&self.key), value));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    pub fn insert_and_get() {
+        let mut tree = RadixTree::new();
+        assert_eq!(tree.insert("utf-8", 8), None);
+        assert_eq!(tree.insert("utf-16", 16), None);
+        assert_eq!(tree.insert("utf", 0), None);
+        assert_eq!(tree.insert("utf-8", 7), Some(8));
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get("utf"), Some(&0));
+        assert_eq!(tree.get("utf-8"), Some(&7));
+        assert_eq!(tree.get("utf-16"), Some(&16));
+        assert_eq!(tree.get("utf-"), None);
+        assert_eq!(tree.get("utf-32"), None);
+        assert_eq!(tree.get(""), None);
+    }
+
+    #[test]
+    pub fn get_or_insert_with() {
+        let mut tree = RadixTree::new();
+        tree.get_or_insert_with("list", Vec::new).push(1);
+        tree.get_or_insert_with("list", Vec::new).push(2);
+        tree.get_or_insert_with("length", Vec::new).push(3);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.get("list"), Some(&vec![1, 2]));
+        assert_eq!(tree.get("length"), Some(&vec![3]));
+    }
+
+    #[test]
+    pub fn remove() {
+        let mut tree = RadixTree::new();
+        for (key, value) in [("a", 1), ("ab", 2), ("abc", 3), ("abd", 4)] {
+            tree.insert(key, value);
+        }
+
+        assert_eq!(tree.remove("ab"), Some(2));
+        assert_eq!(tree.remove("ab"), None);
+        assert_eq!(tree.remove("abcd"), None);
+        assert_eq!(tree.remove("abc"), Some(3));
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            [("a".into(), &1), ("abd".into(), &4)]
+        );
+        assert_eq!(
+            tree.root.edges[0].node.edges[0].label[..],
+            *b"bd",
+            "removing merges edges"
+        );
+    }
+
+    #[test]
+    pub fn iterates_in_order() {
+        let keys = ["", "b", "ba", "a", "ä", "abc", "ab"];
+        let mut tree = RadixTree::new();
+        for key in keys {
+            tree.insert(key, key.len());
+        }
+
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            [
+                (String::new(), &0),
+                ("a".into(), &1),
+                ("ab".into(), &2),
+                ("abc".into(), &3),
+                ("b".into(), &1),
+                ("ba".into(), &2),
+                ("ä".into(), &2),
+            ]
+        );
+
+        assert_eq!(
+            tree.keys().collect::<Vec<_>>(),
+            ["", "a", "ab", "abc", "b", "ba", "ä"]
+        );
+        assert_eq!(
+            tree.into_iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            ["", "a", "ab", "abc", "b", "ba", "ä"]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+/// Identifies a source text in a [SourceMap].
+///
+/// Expressions parsed on their own, outside of any [SourceMap], are in
+/// the default file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Owns the source texts of a program, so that spans can be traced back
+/// to where they came from.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source text, even if one with the same path was already
+    /// added.
+    pub fn add(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len().try_into().expect("fewer than 2^32 files"));
+        self.files.push(SourceFile::new(path.into(), source.into()));
+        id
+    }
+
+    /// # Panics
+    ///
+    /// If the file isn't in this map.
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.index()]
+    }
+
+    /// Like [get](Self::get), but returns [None] if the file isn't in this
+    /// map.
+    pub fn try_get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.index())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (FileId(index as u32), file))
+    }
+
+    /// The location of a character offset in a file, like the start of a
+    /// span.
+    pub fn location(&self, id: FileId, offset: usize) -> Location {
+        self.get(id).location(offset)
+    }
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+
+    /// The character and byte offsets of the start of each line.
+    lines: Vec<(usize, usize)>,
+}
+
+impl SourceFile {
+    fn new(path: PathBuf, source: String) -> Self {
+        let mut lines = vec![(0, 0)];
+        for (offset, (index, c)) in source.char_indices().enumerate() {
+            if c == '\n' {
+                lines.push((offset + 1, index + 1));
+            }
+        }
+
+        Self {
+            path,
+            source,
+            lines,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The text of a zero-based line, without its line break.
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.lines[line].1;
+        let end = self
+            .lines
+            .get(line + 1)
+            .map_or(self.source.len(), |(_, end)| end - 1);
+
+        let text = &self.source[start..end];
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+
+    /// The location of a character offset, as used by spans. Offsets
+    /// past the end are clamped to the end of the source.
+    pub fn location(&self, offset: usize) -> Location {
+        let line = self.lines.partition_point(|(start, _)| *start <= offset) - 1;
+        let (start, byte_start) = self.lines[line];
+        let mut location = Location {
+            line,
+            column: 0,
+            utf8_column: 0,
+            utf16_column: 0,
+        };
+
+        for c in self.source[byte_start..].chars().take(offset - start) {
+            if c == '\n' {
+                break;
+            }
+
+            location.column += 1;
+            location.utf8_column += c.len_utf8();
+            location.utf16_column += c.len_utf16();
+        }
+
+        location
+    }
+}
+
+/// A zero-based line and column in a source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+
+    /// The column in characters.
+    pub column: usize,
+
+    /// The column in UTF-8 bytes.
+    pub utf8_column: usize,
+
+    /// The column in UTF-16 code units, as used by most editors.
+    pub utf16_column: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn location(line: usize, column: usize, utf8_column: usize, utf16_column: usize) -> Location {
+        Location {
+            line,
+            column,
+            utf8_column,
+            utf16_column,
+        }
+    }
+
+    #[test]
+    pub fn assigns_file_ids() {
+        let mut sources = SourceMap::new();
+        let a = sources.add("a.ari", ":a 1");
+        let b = sources.add("b.ari", ":b 2");
+
+        assert_eq!(a, FileId::default());
+        assert_eq!(sources.get(b).source, ":b 2");
+        assert_eq!(
+            sources.files().map(|(id, _)| id).collect::<Vec<_>>(),
+            [a, b]
+        );
+    }
+
+    #[test]
+    pub fn lines() {
+        let mut sources = SourceMap::new();
+        let id = sources.add("lines.ari", ":a 1\r\n:b 2\n\n:c 3");
+        let file = sources.get(id);
+
+        assert_eq!(file.line_count(), 4);
+        assert_eq!(
+            (0..4).map(|line| file.line(line)).collect::<Vec<_>>(),
+            [":a 1", ":b 2", "", ":c 3"]
+        );
+
+        assert_eq!(sources.location(id, 0), location(0, 0, 0, 0));
+        assert_eq!(sources.location(id, 3), location(0, 3, 3, 3));
+        assert_eq!(sources.location(id, 6), location(1, 0, 0, 0));
+        assert_eq!(sources.location(id, 11), location(2, 0, 0, 0));
+        assert_eq!(sources.location(id, 16), location(3, 4, 4, 4));
+        assert_eq!(sources.location(id, 100), location(3, 4, 4, 4));
+    }
+
+    #[test]
+    pub fn unicode_columns() {
+        let mut sources = SourceMap::new();
+        let id = sources.add("unicode.ari", "\"ä🦀\" x");
+
+        assert_eq!(sources.location(id, 2), location(0, 2, 3, 2));
+        assert_eq!(sources.location(id, 3), location(0, 3, 7, 4));
+        assert_eq!(sources.location(id, 5), location(0, 5, 9, 6));
+    }
+}
@@ -0,0 +1,644 @@
+use std::{
+    cmp::Ordering,
+    fmt, mem,
+    ops::{Add, Mul},
+};
+
+use num_traits::{One, Pow, Zero};
+
+use crate::natural::Natural;
+
+/// A natural that's evaluated lazily, so that types with
+/// astronomically many states (like `(^ 256 (^ 2 64))`) never need
+/// to be materialized.
+///
+/// Values that fit in a [Natural] are always [Symbolic::Finite], the
+/// other variants are kept in a normalized form so that equal
+/// expressions are structurally equal as often as possible:
+/// - Sums and products are flattened, sorted, and never contain
+///   zeros or ones.
+/// - Powers of two are always written with a base of `2`, and
+///   products of powers with the same base are merged.
+///
+/// Comparisons are exact but partial: when the order of two values
+/// can't be decided, [PartialOrd::partial_cmp] returns [None] and
+/// they compare unequal.
+#[derive(Debug, Clone)]
+pub enum Symbolic {
+    Finite(Natural),
+    Sum(Box<[Symbolic]>),
+    Product(Box<[Symbolic]>),
+    Power(Box<Symbolic>, Box<Symbolic>),
+}
+
+/// Bounds on the base 2 logarithm of a non-zero value.
+///
+/// When `exact` is set the value is exactly `2^lower`, otherwise it's
+/// strictly between `2^lower` and `2^upper`.
+struct Log2Bounds {
+    lower: Symbolic,
+    upper: Symbolic,
+    exact: bool,
+}
+
+impl Symbolic {
+    /// The materialized value, if it fits in a [Natural].
+    pub fn to_natural(&self) -> Option<&Natural> {
+        match self {
+            Self::Finite(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number of bits needed to encode every state, or [None] if
+    /// it can't be decided.
+    pub fn bit_width(&self) -> Option<Symbolic> {
+        match self {
+            Self::Finite(value) => Some(match value.bit_width() {
+                Some(bits) => Self::from(bits as u64),
+                None => Self::Finite(log2(value)?),
+            }),
+            _ => self
+                .log2_bounds()
+                .filter(|bounds| bounds.exact)
+                .map(|bounds| bounds.lower),
+        }
+    }
+
+    fn log2_bounds(&self) -> Option<Log2Bounds> {
+        match self {
+            Self::Finite(Natural::Unaligned(value)) => {
+                let bits = value.bits();
+                // Unaligned naturals are never one, so zero is the only
+                // unaligned natural with less than two bits
+                (bits > 1).then(|| Log2Bounds {
+                    lower: Self::from(bits - 1),
+                    upper: Self::from(bits),
+                    exact: false,
+                })
+            }
+            Self::Finite(aligned) => {
+                let bits = Self::Finite(log2(aligned)?);
+                Some(Log2Bounds {
+                    lower: bits.clone(),
+                    upper: bits,
+                    exact: true,
+                })
+            }
+            Self::Sum(terms) => {
+                let bounds = terms
+                    .iter()
+                    .map(Self::log2_bounds)
+                    .collect::<Option<Vec<_>>>()?;
+
+                // Every term is non-zero, so the sum is strictly greater
+                // than its biggest term. Terms are never duplicated, so
+                // it's also strictly less than the biggest term times
+                // the number of terms.
+                let lower = max_or_else(bounds.iter().map(|bounds| &bounds.lower), |mut lower| {
+                    lower.next().cloned().unwrap_or_default()
+                });
+                let upper = max_or_else(bounds.iter().map(|bounds| &bounds.upper), |upper| {
+                    upper.cloned().fold(Self::zero(), Add::add)
+                });
+
+                Some(Log2Bounds {
+                    lower,
+                    upper: upper + Self::from(ceil_log2(terms.len() as u64)),
+                    exact: false,
+                })
+            }
+            Self::Product(factors) => factors.iter().try_fold(
+                Log2Bounds {
+                    lower: Self::zero(),
+                    upper: Self::zero(),
+                    exact: true,
+                },
+                |acc, factor| {
+                    let bounds = factor.log2_bounds()?;
+                    Some(Log2Bounds {
+                        lower: acc.lower + bounds.lower,
+                        upper: acc.upper + bounds.upper,
+                        exact: acc.exact && bounds.exact,
+                    })
+                },
+            ),
+            Self::Power(base, exponent) => {
+                let bounds = base.log2_bounds()?;
+                Some(Log2Bounds {
+                    lower: (**exponent).clone() * bounds.lower,
+                    upper: (**exponent).clone() * bounds.upper,
+                    exact: bounds.exact,
+                })
+            }
+        }
+    }
+
+    fn into_terms(self) -> Vec<Self> {
+        match self {
+            Self::Sum(terms) => terms.into_vec(),
+            value => vec![value],
+        }
+    }
+
+    fn into_factors(self) -> Vec<Self> {
+        match self {
+            Self::Product(factors) => factors.into_vec(),
+            value => vec![value],
+        }
+    }
+
+    /// Splits a value into a base and an exponent, normalizing powers
+    /// of two to a base of `2`.
+    fn into_power(self) -> (Self, Self) {
+        match self {
+            Self::Power(base, exponent) => (*base, *exponent),
+            Self::Finite(value) => match log2(&value) {
+                Some(bits) if !bits.is_zero() => (Self::from(2u8), Self::Finite(bits)),
+                _ => (Self::Finite(value), Self::one()),
+            },
+            value => (value, Self::one()),
+        }
+    }
+
+    fn sum(terms: Vec<Self>) -> Self {
+        let terms = group(terms)
+            .into_iter()
+            .map(|(term, count)| match count {
+                1 => term,
+                count => term * Self::from(count as u64),
+            })
+            .collect::<Vec<_>>();
+
+        let mut terms = merge_finite(terms, Natural::checked_add);
+        terms.retain(|term| !term.is_zero());
+        terms.sort_by(Self::structural_cmp);
+        match terms.len() {
+            0 => Self::zero(),
+            1 => terms.pop().unwrap_or_default(),
+            _ => Self::Sum(terms.into_boxed_slice()),
+        }
+    }
+
+    fn product(factors: Vec<Self>) -> Self {
+        let mut powers = Vec::<(Self, Self)>::new();
+        for (base, exponent) in factors.into_iter().map(Self::into_power) {
+            match powers
+                .iter_mut()
+                .find(|(other_base, _)| other_base.structural_cmp(&base).is_eq())
+            {
+                Some((_, other_exponent)) => {
+                    *other_exponent = mem::take(other_exponent) + exponent;
+                }
+                None => powers.push((base, exponent)),
+            }
+        }
+
+        let factors = powers
+            .into_iter()
+            .map(|(base, exponent)| Self::power(base, exponent))
+            .collect::<Vec<_>>();
+
+        let mut factors = merge_finite(factors, Natural::checked_mul);
+        if factors.iter().any(Self::is_zero) {
+            return Self::zero();
+        }
+
+        factors.retain(|factor| !factor.is_one());
+        factors.sort_by(Self::structural_cmp);
+        match factors.len() {
+            0 => Self::one(),
+            1 => factors.pop().unwrap_or_default(),
+            _ => Self::Product(factors.into_boxed_slice()),
+        }
+    }
+
+    /// Builds `base^exponent` from a normalized base, materializing it
+    /// when possible.
+    fn power(base: Self, exponent: Self) -> Self {
+        if exponent.is_zero() || base.is_one() {
+            return Self::one();
+        }
+
+        if exponent.is_one() || base.is_zero() {
+            return base;
+        }
+
+        if let (Self::Finite(base), Self::Finite(exponent)) = (&base, &exponent) {
+            if let Some(value) = base.checked_pow(exponent) {
+                return Self::Finite(value);
+            }
+        }
+
+        Self::Power(Box::new(base), Box::new(exponent))
+    }
+
+    /// A total order on the normalized representation, which is only
+    /// used to sort terms and factors.
+    fn structural_cmp(&self, other: &Self) -> Ordering {
+        fn rank(value: &Symbolic) -> u8 {
+            match value {
+                Symbolic::Finite(_) => 0,
+                Symbolic::Sum(_) => 1,
+                Symbolic::Product(_) => 2,
+                Symbolic::Power(_, _) => 3,
+            }
+        }
+
+        fn cmp_slices(values: &[Symbolic], others: &[Symbolic]) -> Ordering {
+            values
+                .iter()
+                .zip(others)
+                .map(|(value, other)| value.structural_cmp(other))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| values.len().cmp(&others.len()))
+        }
+
+        match (self, other) {
+            (Self::Finite(value), Self::Finite(other)) => value.cmp(other),
+            (Self::Sum(terms), Self::Sum(others)) => cmp_slices(terms, others),
+            (Self::Product(factors), Self::Product(others)) => cmp_slices(factors, others),
+            (Self::Power(base, exponent), Self::Power(other_base, other_exponent)) => base
+                .structural_cmp(other_base)
+                .then_with(|| exponent.structural_cmp(other_exponent)),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+
+    /// Whether the terms of `other` are a strict subset of the terms
+    /// of this sum.
+    fn has_more_terms(&self, other: &Self) -> bool {
+        let Self::Sum(terms) = self else {
+            return false;
+        };
+
+        let other_terms = match other {
+            Self::Sum(other_terms) => other_terms,
+            other => std::slice::from_ref(other),
+        };
+
+        let mut used = vec![false; terms.len()];
+        other_terms.len() < terms.len()
+            && other_terms.iter().all(|other_term| {
+                let found = terms
+                    .iter()
+                    .zip(&used)
+                    .position(|(term, used)| !used && term.structural_cmp(other_term).is_eq());
+
+                found.map(|index| used[index] = true).is_some()
+            })
+    }
+}
+
+/// The exact base 2 logarithm of an aligned natural.
+fn log2(value: &Natural) -> Option<Natural> {
+    match value {
+        Natural::Unaligned(_) => None,
+        Natural::ByteAligned(bytes) => {
+            Natural::from(*bytes as u64).checked_mul(&Natural::from(8u8))
+        }
+        Natural::BitAligned(bits) => Some(Natural::from(*bits as u64)),
+    }
+}
+
+fn ceil_log2(value: u64) -> u64 {
+    u64::from(value.next_power_of_two().trailing_zeros())
+}
+
+/// The biggest value, or the fallback if the maximum can't be decided.
+fn max_or_else<'a, I: Iterator<Item = &'a Symbolic> + Clone>(
+    mut values: I,
+    fallback: impl FnOnce(I) -> Symbolic,
+) -> Symbolic {
+    let all = values.clone();
+    let Some(mut max) = values.next() else {
+        return Symbolic::zero();
+    };
+
+    for value in values {
+        match value.partial_cmp(max) {
+            Some(Ordering::Greater) => max = value,
+            Some(_) => {}
+            None => return fallback(all),
+        }
+    }
+
+    max.clone()
+}
+
+/// Groups structurally equal values, counting their occurrences.
+fn group(mut values: Vec<Symbolic>) -> Vec<(Symbolic, usize)> {
+    values.sort_by(Symbolic::structural_cmp);
+    let mut groups = Vec::<(Symbolic, usize)>::new();
+    for value in values {
+        match groups.last_mut() {
+            Some((last, count)) if last.structural_cmp(&value).is_eq() => *count += 1,
+            _ => groups.push((value, 1)),
+        }
+    }
+
+    groups
+}
+
+/// Combines every finite value while the result can be materialized.
+fn merge_finite(
+    values: Vec<Symbolic>,
+    op: fn(&Natural, &Natural) -> Option<Natural>,
+) -> Vec<Symbolic> {
+    let mut merged = Vec::with_capacity(values.len());
+    let mut finite = None::<Natural>;
+    for value in values {
+        match (value, finite.take()) {
+            (Symbolic::Finite(value), None) => finite = Some(value),
+            (Symbolic::Finite(value), Some(acc)) => match op(&acc, &value) {
+                Some(acc) => finite = Some(acc),
+                None => {
+                    merged.push(Symbolic::Finite(acc));
+                    finite = Some(value);
+                }
+            },
+            (value, acc) => {
+                merged.push(value);
+                finite = acc;
+            }
+        }
+    }
+
+    merged.extend(finite.map(Symbolic::Finite));
+    merged
+}
+
+impl Default for Symbolic {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+/// Writes the value as an ari expression, like `(^ 2 2^67)`.
+impl fmt::Display for Symbolic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (head, args) = match self {
+            Self::Finite(value) => return value.fmt(f),
+            Self::Sum(terms) => ("+", terms),
+            Self::Product(factors) => ("*", factors),
+            Self::Power(base, exponent) => return write!(f, "(^ {base} {exponent})"),
+        };
+
+        write!(f, "({head}")?;
+        for arg in args.iter() {
+            write!(f, " {arg}")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl PartialEq for Symbolic {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Symbolic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if let (Self::Finite(value), Self::Finite(other)) = (self, other) {
+            return Some(value.cmp(other));
+        }
+
+        if self.structural_cmp(other).is_eq() {
+            return Some(Ordering::Equal);
+        }
+
+        // Only finite values can be zero
+        if self.is_zero() {
+            return Some(Ordering::Less);
+        }
+
+        if other.is_zero() {
+            return Some(Ordering::Greater);
+        }
+
+        if self.has_more_terms(other) {
+            return Some(Ordering::Greater);
+        }
+
+        if other.has_more_terms(self) {
+            return Some(Ordering::Less);
+        }
+
+        let bounds = self.log2_bounds()?;
+        let other_bounds = other.log2_bounds()?;
+        if bounds.exact && other_bounds.exact {
+            return bounds.lower.partial_cmp(&other_bounds.lower);
+        }
+
+        let strict = |upper: &Self, lower: &Self| match upper.partial_cmp(lower)? {
+            Ordering::Less => Some(true),
+            Ordering::Equal => Some(!bounds.exact || !other_bounds.exact),
+            Ordering::Greater => None,
+        };
+
+        if let Some(true) = strict(&bounds.upper, &other_bounds.lower) {
+            Some(Ordering::Less)
+        } else if let Some(true) = strict(&other_bounds.upper, &bounds.lower) {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl Zero for Symbolic {
+    fn zero() -> Self {
+        Self::Finite(Natural::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        matches!(self, Self::Finite(value) if value.is_zero())
+    }
+}
+
+impl One for Symbolic {
+    fn one() -> Self {
+        Self::Finite(Natural::one())
+    }
+
+    fn is_one(&self) -> bool {
+        matches!(self, Self::Finite(value) if value.is_one())
+    }
+}
+
+impl Add for Symbolic {
+    type Output = Symbolic;
+
+    fn add(self, other: Self) -> Self::Output {
+        if let (Self::Finite(value), Self::Finite(other_value)) = (&self, &other) {
+            if let Some(value) = value.checked_add(other_value) {
+                return Self::Finite(value);
+            }
+        }
+
+        let mut terms = self.into_terms();
+        terms.extend(other.into_terms());
+        Self::sum(terms)
+    }
+}
+
+impl Mul for Symbolic {
+    type Output = Symbolic;
+
+    fn mul(self, other: Self) -> Self::Output {
+        if let (Self::Finite(value), Self::Finite(other_value)) = (&self, &other) {
+            if let Some(value) = value.checked_mul(other_value) {
+                return Self::Finite(value);
+            }
+        }
+
+        let mut factors = self.into_factors();
+        factors.extend(other.into_factors());
+        Self::product(factors)
+    }
+}
+
+impl Pow<Symbolic> for Symbolic {
+    type Output = Symbolic;
+
+    fn pow(self, exponent: Symbolic) -> Self::Output {
+        if let (Self::Finite(value), Self::Finite(exponent)) = (&self, &exponent) {
+            if let Some(value) = value.checked_pow(exponent) {
+                return Self::Finite(value);
+            }
+        }
+
+        match self {
+            // (a * b)^n = a^n * b^n
+            Self::Product(factors) => Self::product(
+                factors
+                    .into_vec()
+                    .into_iter()
+                    .map(|factor| factor.pow(exponent.clone()))
+                    .collect(),
+            ),
+            // (a^m)^n = a^(m * n)
+            value => {
+                let (base, inner_exponent) = value.into_power();
+                Self::power(base, inner_exponent * exponent)
+            }
+        }
+    }
+}
+
+impl From<Natural> for Symbolic {
+    fn from(value: Natural) -> Self {
+        Self::Finite(value)
+    }
+}
+
+impl From<u8> for Symbolic {
+    fn from(value: u8) -> Self {
+        Self::Finite(Natural::from(value))
+    }
+}
+
+impl From<u16> for Symbolic {
+    fn from(value: u16) -> Self {
+        Self::Finite(Natural::from(value))
+    }
+}
+
+impl From<u32> for Symbolic {
+    fn from(value: u32) -> Self {
+        Self::Finite(Natural::from(value))
+    }
+}
+
+impl From<u64> for Symbolic {
+    fn from(value: u64) -> Self {
+        Self::Finite(Natural::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn huge() -> Symbolic {
+        // (^ 256 (^ 2 64))
+        Symbolic::from(256u16).pow(Symbolic::from(2u8).pow(Symbolic::from(64u8)))
+    }
+
+    #[test]
+    pub fn finite() {
+        assert_eq!(
+            Symbolic::from(3u8) + Symbolic::from(5u8),
+            Symbolic::Finite(Natural::BitAligned(3))
+        );
+        assert_eq!(
+            Symbolic::from(256u16).pow(Symbolic::from(2u8)),
+            Symbolic::Finite(Natural::ByteAligned(2))
+        );
+    }
+
+    #[test]
+    pub fn huge_power() {
+        let Symbolic::Power(base, exponent) = huge() else {
+            panic!("expected a power");
+        };
+
+        assert_eq!(base.to_natural(), Some(&Natural::from(2u8)));
+        assert_eq!(exponent.to_natural(), Some(&Natural::BitAligned(67)));
+        assert_eq!(
+            huge().bit_width(),
+            Some(Symbolic::from(Natural::BitAligned(67)))
+        );
+    }
+
+    #[test]
+    pub fn equal_powers() {
+        // (^ 16 (^ 2 65)) = (^ 256 (^ 2 64))
+        assert_eq!(
+            Symbolic::from(16u8).pow(Symbolic::from(2u8).pow(Symbolic::from(65u8))),
+            huge()
+        );
+        assert_eq!(huge() * huge(), huge().pow(Symbolic::from(2u8)));
+    }
+
+    #[test]
+    pub fn cmp_powers() {
+        let unaligned = Symbolic::from(255u8).pow(Symbolic::from(2u8).pow(Symbolic::from(64u8)));
+        assert!(unaligned < huge());
+        assert!(huge() > Symbolic::from(Natural::ByteAligned(usize::MAX)));
+        assert!(huge() < huge() * Symbolic::from(2u8));
+        assert!(Symbolic::zero() < huge());
+    }
+
+    #[test]
+    pub fn cmp_sums() {
+        let sum = huge() + Symbolic::one();
+        assert!(sum > huge());
+        assert!(sum < huge() * Symbolic::from(2u8));
+        assert_eq!(huge() + huge(), huge() * Symbolic::from(2u8));
+        assert_eq!(sum.bit_width(), None);
+    }
+
+    #[test]
+    pub fn undecided() {
+        let three = Symbolic::from(3u8).pow(Symbolic::from(Natural::BitAligned(70)));
+        let five = Symbolic::from(5u8).pow(Symbolic::from(Natural::BitAligned(69)));
+        assert_eq!(three.partial_cmp(&five), None);
+        assert!(three != five);
+        assert_eq!(three.bit_width(), None);
+    }
+
+    #[test]
+    pub fn display() {
+        assert_eq!(Symbolic::from(256u16).to_string(), "256");
+        assert_eq!(huge().to_string(), "(^ 2 2^67)");
+        assert_eq!(
+            (huge() + Symbolic::one()).to_string(),
+            "(+ 1 (^ 2 2^67))"
+        );
+    }
+}
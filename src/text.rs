@@ -0,0 +1,162 @@
+use crate::natural::Natural;
+
+/// A text encoding context, which determines how [text
+/// expressions](crate::ast::ExprVariant::Text) are desugared into
+/// code units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Ascii7,
+    Ascii8,
+    #[default]
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    /// Looks up the encoding introduced by a text encoding macro.
+    pub fn from_macro(name: &str) -> Option<Self> {
+        Some(match name {
+            "ascii-7" => Self::Ascii7,
+            "ascii" | "ascii-8" => Self::Ascii8,
+            "utf-8" => Self::Utf8,
+            "utf-16" => Self::Utf16,
+            "utf-32" => Self::Utf32,
+            _ => return None,
+        })
+    }
+
+    /// The number of possible states for a single code unit.
+    pub fn code_unit(self) -> Natural {
+        match self {
+            Self::Ascii7 => Natural::from(0x80u8),
+            Self::Ascii8 | Self::Utf8 => Natural::from(0x100u16),
+            Self::Utf16 => Natural::from(0x10000u32),
+            Self::Utf32 => Natural::from(0x100000000u64),
+        }
+    }
+
+    pub fn encode(self, text: &str) -> Result<EncodedText, EncodeError> {
+        let code_units = match self {
+            Self::Ascii7 | Self::Ascii8 => {
+                let max = if self == Self::Ascii7 { 0x7f } else { 0xff };
+                text.chars()
+                    .enumerate()
+                    .map(|(index, c)| match u32::from(c) {
+                        code_unit if code_unit <= max => Ok(code_unit),
+                        _ => Err(EncodeError { index, found: c }),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            Self::Utf8 => text.bytes().map(u32::from).collect(),
+            Self::Utf16 => text.encode_utf16().map(u32::from).collect(),
+            Self::Utf32 => text.chars().map(u32::from).collect(),
+        };
+
+        Ok(EncodedText {
+            encoding: self,
+            code_units,
+        })
+    }
+}
+
+/// Text desugared into a product of code units, where each code unit
+/// asserts a single value of the encoding's [code
+/// unit](Encoding::code_unit) type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodedText {
+    pub encoding: Encoding,
+    pub code_units: Box<[u32]>,
+}
+
+impl EncodedText {
+    /// Decodes the code units back into text. Encodings can't produce
+    /// invalid code units, so this is only lossy for code units that
+    /// weren't encoded by [Encoding::encode].
+    pub fn decode(&self) -> String {
+        match self.encoding {
+            Encoding::Ascii7 | Encoding::Ascii8 | Encoding::Utf32 => self
+                .code_units
+                .iter()
+                .map(|&code_unit| char::from_u32(code_unit).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+            Encoding::Utf8 => {
+                let bytes = self.code_units.iter().map(|&code_unit| code_unit as u8);
+                String::from_utf8_lossy(&bytes.collect::<Vec<_>>()).into_owned()
+            }
+            Encoding::Utf16 => {
+                let code_units = self.code_units.iter().map(|&code_unit| code_unit as u16);
+                String::from_utf16_lossy(&code_units.collect::<Vec<_>>())
+            }
+        }
+    }
+}
+
+/// A character that can't be represented in the encoding context.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodeError {
+    pub index: usize,
+    pub found: char,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    pub fn utf8() {
+        assert_eq!(
+            Encoding::Utf8.encode("aé"),
+            Ok(EncodedText {
+                encoding: Encoding::Utf8,
+                code_units: Box::new([0x61, 0xc3, 0xa9]),
+            })
+        );
+    }
+
+    #[test]
+    pub fn utf16() {
+        assert_eq!(
+            Encoding::Utf16.encode("a🙃"),
+            Ok(EncodedText {
+                encoding: Encoding::Utf16,
+                code_units: Box::new([0x61, 0xd83d, 0xde43]),
+            })
+        );
+    }
+
+    #[test]
+    pub fn utf32() {
+        assert_eq!(
+            Encoding::Utf32.encode("a🙃"),
+            Ok(EncodedText {
+                encoding: Encoding::Utf32,
+                code_units: Box::new([0x61, 0x1f643]),
+            })
+        );
+    }
+
+    #[test]
+    pub fn ascii8() {
+        assert_eq!(
+            Encoding::Ascii8.encode("aé"),
+            Ok(EncodedText {
+                encoding: Encoding::Ascii8,
+                code_units: Box::new([0x61, 0xe9]),
+            })
+        );
+    }
+
+    #[test]
+    pub fn ascii7_out_of_range() {
+        assert_eq!(
+            Encoding::Ascii7.encode("aé"),
+            Err(EncodeError {
+                index: 1,
+                found: 'é'
+            })
+        );
+    }
+}
@@ -0,0 +1,3 @@
+mod cli {
+    #[rustfmt::skip] mod commands;
+}
@@ -0,0 +1,262 @@
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use pretty_assertions::assert_eq;
+
+/// Runs `ari` with a program on stdin, returning its exit code, stdout
+/// and stderr.
+fn ari(args: &[&str], stdin: &str) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ari"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Writes a file to a directory of its own, returning its path.
+fn file(dir: &str, name: &str, source: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, source).unwrap();
+    path
+}
+
+#[test]
+fn check() {
+    assert_eq!(
+        ari(&["check"], ":a 1 :b (+ a 2)"),
+        (0, String::new(), String::new())
+    );
+
+    let (code, stdout, stderr) = ari(&["check"], ":a b");
+    assert_eq!((code, stdout.as_str()), (1, ""));
+    assert!(stderr.starts_with("error[E0008]: unresolved symbol\n --> <stdin>:1:4\n"));
+}
+
+#[test]
+fn file_argument() {
+    let path = file("file_argument", "main.ari", ":a 1 :b (* a 2)");
+    let path = path.to_str().unwrap();
+    assert_eq!(ari(&["check", path], ""), (0, String::new(), String::new()));
+    assert_eq!(
+        ari(&["eval", path, "b"], ""),
+        (0, "type: 2\nstates: 2\n".to_string(), String::new())
+    );
+
+    assert_eq!(
+        ari(&["ast", path], ""),
+        (
+            0,
+            "0..4 :a natural 1\n5..15 :b sexpr\n  9..10 reference 2 -20\n  11..12 reference 1 -1\n  13..14 natural 2\n"
+                .to_string(),
+            String::new()
+        )
+    );
+
+    let (code, stdout, stderr) = ari(&["check", "missing.ari"], "");
+    assert_eq!((code, stdout.as_str()), (1, ""));
+    assert!(stderr.starts_with("error: couldn't read missing.ari: "));
+}
+
+#[test]
+fn closed_stdout() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ari"))
+        .arg("ast")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Nothing is written until stdin is closed
+    drop(child.stdout.take());
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            (0..1000)
+                .map(|i| format!(":a{i} {i}\n"))
+                .collect::<String>()
+                .as_bytes(),
+        )
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(
+        (
+            output.status.code(),
+            String::from_utf8(output.stderr).unwrap()
+        ),
+        (Some(0), String::new())
+    );
+}
+
+#[test]
+fn ast() {
+    let src = ":a (* :x 0xFF :y \"q\"\"\") :b a:y @a:x :c (* 2 $b)";
+    assert_eq!(
+        ari(&["ast"], src),
+        (
+            0,
+            "\
+0..23 :a sexpr
+  4..5 reference 2 -20
+  6..13 :x natural 255
+  14..22 :y text \"q\"\"\"
+24..30 :b reference 0 -1 2
+31..35 deref reference 0 -2 1
+36..47 :c sexpr
+  40..41 reference 2 -20
+  42..43 natural 2
+  44..46 value-ref 1 -2
+"
+            .to_string(),
+            String::new()
+        )
+    );
+
+    let (code, stdout, stderr) = ari(&["ast"], "x");
+    assert_eq!((code, stdout.as_str()), (1, "0..1 unresolved x\n"));
+    assert!(stderr.starts_with("error[E0008]: "));
+}
+
+#[test]
+fn eval() {
+    assert_eq!(
+        ari(&["eval", "pair"], ":byte 256 :pair (* byte byte)"),
+        (
+            0,
+            "type: (* 256 256)\nstates: 65536\n".to_string(),
+            String::new()
+        )
+    );
+
+    assert_eq!(
+        ari(&["check"], ":l (+ 1 (* 256 l))"),
+        (0, String::new(), String::new())
+    );
+    assert_eq!(
+        ari(&["eval", "l"], ":l (+ 1 (* 256 l))"),
+        (
+            0,
+            "type: (+ 1 (* 256 '<recursive>'))\nstates: unbounded\n".to_string(),
+            String::new()
+        )
+    );
+
+    let (code, stdout, stderr) = ari(&["eval", "a"], ":a (* 0 2)");
+    assert_eq!((code, stdout.as_str()), (1, ""));
+    assert!(stderr.starts_with("error[E0101]: uncaught bottom type\n"));
+
+    assert_eq!(
+        ari(&["eval", "b"], ":a (+ 1 2) :b (+ a (+ 3 4)) :c (* 0 2)"),
+        (
+            0,
+            "type: (+ 1 2 3 4)\nstates: 10\n".to_string(),
+            String::new()
+        )
+    );
+
+    assert_eq!(
+        ari(&["eval", "b"], ":a 1"),
+        (
+            1,
+            String::new(),
+            "error: nothing is labelled `b`\n".to_string()
+        )
+    );
+}
+
+#[test]
+fn fmt() {
+    assert_eq!(
+        ari(&["fmt"], ":a   (+ 1   2)"),
+        (0, ":a (+ 1 2)\n".to_string(), String::new())
+    );
+}
+
+#[test]
+fn fmt_check() {
+    let formatted = file("fmt_check", "formatted.ari", ":a (+ 1 2)\n");
+    let unformatted = file("fmt_check", "unformatted.ari", ":a   (+ 1 2)");
+    let (formatted, unformatted) = (formatted.to_str().unwrap(), unformatted.to_str().unwrap());
+    assert_eq!(
+        ari(&["fmt", "--check", formatted], ""),
+        (0, String::new(), String::new())
+    );
+    assert_eq!(
+        ari(&["fmt", "--check", formatted, unformatted], ""),
+        (1, format!("{unformatted}\n"), String::new())
+    );
+    assert_eq!(fs::read_to_string(unformatted).unwrap(), ":a   (+ 1 2)");
+
+    let (code, stdout, stderr) = ari(&["fmt", "--check"], ":a (");
+    assert_eq!((code, stdout.as_str()), (1, ""));
+    assert!(stderr.starts_with("error[E0001]: "));
+}
+
+#[test]
+fn fmt_refuses_any_parse_error() {
+    for (src, code) in [("1__0", "E0006"), ("''", "E0002"), (":a 1 :a 2", "E0003")] {
+        let (status, stdout, stderr) = ari(&["fmt"], src);
+        assert_eq!((status, stdout.as_str()), (1, ""), "{src}");
+        assert!(stderr.starts_with(&format!("error[{code}]: ")), "{stderr}");
+    }
+
+    // References are only resolved once imports are loaded
+    assert_eq!(
+        ari(&["fmt"], "x  y"),
+        (0, "x\ny\n".to_string(), String::new())
+    );
+}
+
+#[test]
+fn fmt_in_place() {
+    let formatted = file("fmt_in_place", "formatted.ari", ":a 1\n");
+    let unformatted = file("fmt_in_place", "unformatted.ari", "; one\n:a   1 :b 0xFF");
+    assert_eq!(
+        ari(
+            &[
+                "fmt",
+                formatted.to_str().unwrap(),
+                unformatted.to_str().unwrap()
+            ],
+            ""
+        ),
+        (0, String::new(), String::new())
+    );
+    assert_eq!(fs::read_to_string(formatted).unwrap(), ":a 1\n");
+    assert_eq!(
+        fs::read_to_string(unformatted).unwrap(),
+        "; one\n:a 1\n:b 0xFF\n"
+    );
+}
+
+#[test]
+fn usage() {
+    let (code, stdout, stderr) = ari(&["eval"], "");
+    assert_eq!((code, stdout.as_str()), (2, ""));
+    assert!(stderr.starts_with("usage: ari <command> [args]\n"));
+}
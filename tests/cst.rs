@@ -0,0 +1,4 @@
+mod cst {
+    #[rustfmt::skip] mod comments;
+    #[rustfmt::skip] mod round_trip;
+}
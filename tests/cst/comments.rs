@@ -0,0 +1,64 @@
+use pretty_assertions::assert_eq;
+
+use ari::{ast::ExprVariant, cst::Cst};
+
+/// The text of the comments attached to each expression of the root
+/// scope.
+fn comments(src: &str) -> Vec<Vec<String>> {
+    let cst = Cst::parse(src);
+    let scope = cst.scope().0.unwrap();
+    scope
+        .exprs()
+        .iter()
+        .map(|expr| {
+            cst.comments(expr)
+                .iter()
+                .map(|token| token.text.clone())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn leading_comments() {
+    assert_eq!(
+        comments("; the size\n; in bits\n:size 8\n#| a byte |#\n:byte (^ 256 size)"),
+        [
+            vec!["; the size".to_owned(), "; in bits".to_owned()],
+            vec!["#| a byte |#".to_owned()],
+        ]
+    );
+}
+
+#[test]
+fn trailing_comments_belong_to_previous_expr() {
+    assert_eq!(
+        comments(":a 1 ; one\n; two\n:b 2 #| b |# :c 3"),
+        [vec![], vec!["; two".to_owned()], vec![]]
+    );
+}
+
+#[test]
+fn labels_come_first() {
+    assert_eq!(
+        comments("; labelled\n:x :y 1"),
+        [vec!["; labelled".to_owned()]]
+    );
+}
+
+#[test]
+fn comments_after_left_paren_come_first() {
+    let cst = Cst::parse("(; c\n + 1)");
+    let scope = cst.scope().0.unwrap();
+    let ExprVariant::SExpr(scope) = &scope.exprs()[0].base.variant else {
+        panic!("not an s-expression");
+    };
+
+    assert_eq!(
+        cst.comments(&scope.exprs()[0])
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect::<Vec<_>>(),
+        ["; c"]
+    );
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::ExprVariant,
+    cst::{Cst, Node, TokenKind},
+};
+
+fn round_trip(src: &str) {
+    assert_eq!(Cst::parse(src).to_string(), src);
+}
+
+#[test]
+fn every_token() {
+    round_trip(
+        "; a chunk\n:chunk (* :length 0x20 :data (^ 256 $length)) #| nested #| block |# |#\n\
+         :value @chunk:length\n:name 'ünïcödé name' :text \"a \"\"quoted\"\" text\"\n\\chunk",
+    );
+}
+
+#[test]
+fn malformed() {
+    round_trip(")) :a (+ (1 2");
+    round_trip(":text \"never closed");
+    round_trip("#| never closed #| |#");
+    round_trip("\r\n\t");
+}
+
+#[test]
+fn tokens() {
+    let cst = Cst::parse(":a (+ 1 'b') ;c");
+    assert_eq!(
+        cst.tokens()
+            .iter()
+            .map(|token| (token.kind, token.span.clone()))
+            .collect::<Vec<_>>(),
+        [
+            (TokenKind::Colon, 0..1),
+            (TokenKind::Symbol, 1..2),
+            (TokenKind::Whitespace, 2..3),
+            (TokenKind::LeftParen, 3..4),
+            (TokenKind::Symbol, 4..5),
+            (TokenKind::Whitespace, 5..6),
+            (TokenKind::Natural, 6..7),
+            (TokenKind::Whitespace, 7..8),
+            (TokenKind::QuotedSymbol, 8..11),
+            (TokenKind::RightParen, 11..12),
+            (TokenKind::Whitespace, 12..13),
+            (TokenKind::LineComment, 13..15),
+        ]
+    );
+}
+
+#[test]
+fn tokens_end_where_exprs_do() {
+    let cst = Cst::parse("1#|c|#");
+    assert_eq!(
+        cst.tokens()
+            .iter()
+            .map(|token| (token.kind, token.span.clone()))
+            .collect::<Vec<_>>(),
+        [(TokenKind::Natural, 0..1), (TokenKind::BlockComment, 1..6)]
+    );
+}
+
+#[test]
+fn spans_agree() {
+    for src in [
+        "; a chunk\n:chunk (* :length 0x20 :data (^ 256 $length)) #| nested #| block |# |#\n\
+         :value @chunk:length\n:name 'ünïcödé name' :text \"a \"\"quoted\"\" text\"",
+        ":a 1#|c|# (+ 2^8#|c|# 'a'#|c|# \"b\";c\n)",
+        "0x1_0 (0b1) 0123 \\\\ \\a",
+    ] {
+        let cst = Cst::parse(src);
+        let boundaries = cst
+            .tokens()
+            .iter()
+            .flat_map(|token| [token.span.start, token.span.end])
+            .collect::<HashSet<_>>();
+
+        let scope = cst.scope().0.unwrap();
+        let mut exprs = scope.exprs().iter().collect::<Vec<_>>();
+        while let Some(expr) = exprs.pop() {
+            let spans = expr.labels.iter().map(|label| label.span.clone());
+            for span in spans.chain([expr.base.span.clone()]) {
+                assert!(
+                    boundaries.contains(&span.start) && boundaries.contains(&span.end),
+                    "{span:?} of {src:?} splits a token"
+                );
+            }
+
+            if let ExprVariant::SExpr(scope) = &expr.base.variant {
+                exprs.extend(scope.exprs());
+            }
+        }
+    }
+}
+
+#[test]
+fn nodes() {
+    let cst = Cst::parse("(a (b)) ) (");
+    assert_eq!(
+        cst.nodes(),
+        [
+            Node::List {
+                open: 0,
+                children: vec![
+                    Node::Token(1),
+                    Node::Token(2),
+                    Node::List {
+                        open: 3,
+                        children: vec![Node::Token(4)],
+                        close: Some(5),
+                    },
+                ],
+                close: Some(6),
+            },
+            Node::Token(7),
+            Node::Token(8),
+            Node::Token(9),
+            Node::List {
+                open: 10,
+                children: vec![],
+                close: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn scope_spans_match_tokens() {
+    let cst = Cst::parse("; size\n:byte 256");
+    let (scope, errors) = cst.scope();
+    assert_eq!(errors, []);
+
+    let scope = scope.unwrap();
+    let expr = &scope.exprs()[0];
+    let natural = cst
+        .tokens()
+        .iter()
+        .find(|token| token.kind == TokenKind::Natural)
+        .unwrap();
+
+    assert_eq!(expr.base.span, natural.span);
+}
@@ -0,0 +1,6 @@
+mod eval {
+    #[rustfmt::skip] mod algebra;
+    #[rustfmt::skip] mod assertions;
+    #[rustfmt::skip] mod references;
+    #[rustfmt::skip] mod texts;
+}
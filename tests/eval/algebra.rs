@@ -0,0 +1,162 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    eval::{eval, Error, Type},
+    natural::Natural,
+    parser::parser,
+    prelude::Builtin,
+    symbolic::Symbolic,
+};
+
+use chumsky::Parser;
+use num_traits::Pow;
+
+#[test]
+fn natural() {
+    assert_eq!(
+        eval(&parser().parse("256").unwrap()).into_vec(),
+        vec![Ok(Type::natural(256u16))],
+    );
+}
+
+#[test]
+fn sum() {
+    let ty = Type::sum([Type::natural(1u8), Type::natural(1u8), Type::natural(1u8)]);
+
+    assert_eq!(ty.states, Some(Symbolic::from(3u8)));
+    assert_eq!(
+        eval(
+            &parser()
+                .parse(":size (+ :small 1 :medium 1 :large 1)")
+                .unwrap()
+        )
+        .into_vec(),
+        vec![Ok(ty)],
+    );
+}
+
+#[test]
+fn nested_sums_are_flattened() {
+    assert_eq!(
+        eval(&parser().parse("(+ (+ 1 2) 0 3)").unwrap()).into_vec(),
+        vec![Ok(Type::sum([
+            Type::natural(1u8),
+            Type::natural(2u8),
+            Type::natural(3u8),
+        ]))],
+    );
+}
+
+#[test]
+fn product() {
+    let ty = Type::product([
+        Type::natural(256u16),
+        Type::natural(256u16),
+        Type::natural(256u16),
+    ]);
+
+    assert_eq!(ty.states, Some(Symbolic::from(Natural::ByteAligned(3))));
+    assert_eq!(
+        eval(&parser().parse("(* :r 256 :g 256 1 :b 256)").unwrap()).into_vec(),
+        vec![Ok(ty)],
+    );
+}
+
+#[test]
+fn map() {
+    let ty = Type::map(
+        Type::natural(2u8),
+        Type::product([Type::natural(8u8), Type::natural(2u8)]),
+    );
+
+    assert_eq!(ty.states, Some(Symbolic::from(Natural::ByteAligned(2))));
+    assert_eq!(
+        eval(&parser().parse("(^ 2 8 2)").unwrap()).into_vec(),
+        vec![Ok(ty.clone())],
+    );
+    assert_eq!(
+        eval(&parser().parse("(^ (^ 2 8) 2)").unwrap()).into_vec(),
+        vec![Ok(ty)],
+    );
+}
+
+#[test]
+fn huge_map() {
+    let [Ok(ty)] = &*eval(&parser().parse("(^ 256 (^ 2 64))").unwrap()) else {
+        panic!("expected a single type");
+    };
+
+    assert_eq!(
+        ty.states,
+        Some(Symbolic::from(256u16).pow(Symbolic::from(Natural::ByteAligned(8))))
+    );
+}
+
+#[test]
+fn huge_unaligned_map_stays_symbolic() {
+    let types = eval(&parser().parse(":a 7 :b (^ a 100000000)").unwrap());
+    let Ok(ty) = &types[1] else {
+        panic!("expected a type");
+    };
+
+    assert_eq!(
+        ty.states,
+        Some(Symbolic::from(7u8).pow(Symbolic::from(100000000u32)))
+    );
+    assert!(matches!(ty.states, Some(Symbolic::Power(_, _))));
+}
+
+#[test]
+fn bottom_propagates_through_products() {
+    assert_eq!(
+        eval(&parser().parse("(* 256 (* 256 0))").unwrap()).into_vec(),
+        vec![Err(Error::bottom(14..15))],
+    );
+}
+
+#[test]
+fn bottom_propagates_through_map_bases() {
+    assert_eq!(
+        eval(&parser().parse("(^ 0 8) (^ 8 0)").unwrap()).into_vec(),
+        vec![Err(Error::bottom(3..4)), Ok(Type::natural(1u8))],
+    );
+}
+
+#[test]
+fn bottom_is_caught_by_sums() {
+    assert_eq!(
+        eval(&parser().parse("(+ 256 (* 256 0))").unwrap()).into_vec(),
+        vec![Ok(Type::natural(256u16))],
+    );
+}
+
+#[test]
+fn unknown_function() {
+    assert_eq!(
+        eval(&parser().parse(":f 2 (f 1) ()").unwrap()).into_vec(),
+        vec![
+            Ok(Type::natural(2u8)),
+            Err(Error::unknown_function(6..7)),
+            Err(Error::unknown_function(11..13)),
+        ],
+    );
+}
+
+#[test]
+fn unsupported_builtin() {
+    assert_eq!(
+        eval(&parser().parse("(- 2 1)").unwrap()).into_vec(),
+        vec![Err(Error::unsupported(1..2))],
+    );
+}
+
+#[test]
+fn aliased_builtin() {
+    assert_eq!(
+        eval(&parser().parse(":sum + (sum 1 2)").unwrap()).into_vec(),
+        vec![
+            Ok(Type::builtin(Builtin::Add)),
+            Ok(Type::sum([Type::natural(1u8), Type::natural(2u8)])),
+        ],
+    );
+}
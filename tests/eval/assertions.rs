@@ -0,0 +1,48 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    eval::{eval, Error, Type},
+    parser::parser,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn assertion() {
+    assert_eq!(
+        eval(&parser().parse("(= 256 (^ 2 8))").unwrap()).into_vec(),
+        vec![Ok(Type::map(Type::natural(2u8), Type::natural(8u8)))],
+    );
+}
+
+#[test]
+fn failed_assertion() {
+    assert_eq!(
+        eval(&parser().parse("(= 256 255)").unwrap()).into_vec(),
+        vec![Err(Error::assertion_failed(0..11))],
+    );
+}
+
+#[test]
+fn failed_assertion_is_caught_by_sums() {
+    assert_eq!(
+        eval(&parser().parse("(+ 1 (= 2 3))").unwrap()).into_vec(),
+        vec![Ok(Type::natural(1u8))],
+    );
+}
+
+#[test]
+fn undecidable_assertion() {
+    assert_eq!(
+        eval(&parser().parse("(= (^ 3 2^70) (^ 5 2^69))").unwrap()).into_vec(),
+        vec![Err(Error::undecidable(0..25))],
+    );
+}
+
+#[test]
+fn dependent_assertion() {
+    assert_eq!(
+        eval(&parser().parse(":x 256 (= @x 3)").unwrap()).into_vec(),
+        vec![Ok(Type::natural(256u16)), Ok(Type::natural(3u8))],
+    );
+}
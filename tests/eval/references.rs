@@ -0,0 +1,153 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    eval::{eval, eval_at, Error, Type},
+    parser::{open_parser, parser},
+    symbolic::Symbolic,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn reference() {
+    let pixel = Type::product([
+        Type::natural(256u16),
+        Type::natural(256u16),
+        Type::natural(256u16),
+    ]);
+
+    assert_eq!(
+        eval(
+            &parser()
+                .parse(":pixel (* :r byte :g byte :b byte) :byte 256")
+                .unwrap()
+        )
+        .into_vec(),
+        vec![Ok(pixel), Ok(Type::natural(256u16))],
+    );
+}
+
+#[test]
+fn eval_one_expr() {
+    let scope = parser().parse(":bottom (* 0 2) :a (* 3 b) :b 5").unwrap();
+    assert_eq!(
+        eval_at(&scope, 1),
+        Ok(Type::product([Type::natural(3u8), Type::natural(5u8)])),
+    );
+    assert_eq!(eval_at(&scope, 1), eval(&scope)[1]);
+}
+
+#[test]
+fn path_reference() {
+    assert_eq!(
+        eval(&parser().parse(":a (* :x 3 :y 5) :b a:y").unwrap()).into_vec(),
+        vec![
+            Ok(Type::product([Type::natural(3u8), Type::natural(5u8)])),
+            Ok(Type::natural(5u8)),
+        ],
+    );
+}
+
+#[test]
+fn reference_in_parent_scope() {
+    assert_eq!(
+        eval(&parser().parse(":a 3 (* (+ a 1) 2)").unwrap()).into_vec(),
+        vec![
+            Ok(Type::natural(3u8)),
+            Ok(Type::product([
+                Type::sum([Type::natural(3u8), Type::natural(1u8)]),
+                Type::natural(2u8),
+            ])),
+        ],
+    );
+}
+
+#[test]
+fn references_at_different_depths() {
+    let types = eval(
+        &parser()
+            .parse(":a 2 :b 3 (* (* :c 5 a (* b (* a c))) (* b a) (* :a 7 a \\a) a)")
+            .unwrap(),
+    );
+
+    assert_eq!(
+        types[2].as_ref().unwrap().states,
+        Some(Symbolic::from(300 * 6 * 98 * 2u32))
+    );
+}
+
+#[test]
+fn unresolved_reference() {
+    assert_eq!(
+        eval(&open_parser().parse("(* x 2)").unwrap()).into_vec(),
+        vec![Err(Error::unresolved_symbol(3..4, "x"))],
+    );
+}
+
+#[test]
+fn direct_cycle() {
+    assert_eq!(
+        eval(&parser().parse(":a a").unwrap()).into_vec(),
+        vec![Err(Error::cycle(3..4))],
+    );
+}
+
+#[test]
+fn indirect_cycle() {
+    assert_eq!(
+        eval(&open_parser().parse(":a (* 2 b) :b (* 1 a)").unwrap()).into_vec(),
+        vec![Err(Error::cycle(3..10)), Err(Error::cycle(3..10))],
+    );
+}
+
+#[test]
+fn guarded_cycle() {
+    let list = Type::sum([
+        Type::natural(1u8),
+        Type::product([Type::natural(256u16), Type::recursive()]),
+    ]);
+
+    let scope = parser().parse(":l (+ 1 (* 256 l))").unwrap();
+    assert_eq!(eval(&scope).into_vec(), vec![Ok(list.clone())]);
+    assert_eq!(list.states, None);
+    assert!(list.is_recursive());
+}
+
+#[test]
+fn indirect_guarded_cycle() {
+    let a = Type::product([
+        Type::natural(2u8),
+        Type::sum([Type::natural(1u8), Type::recursive()]),
+    ]);
+
+    let b = Type::sum([
+        Type::natural(1u8),
+        Type::product([Type::natural(2u8), Type::recursive()]),
+    ]);
+
+    assert_eq!(
+        eval(&parser().parse(":a (* 2 b) :b (+ 1 a)").unwrap()).into_vec(),
+        vec![Ok(a), Ok(b)],
+    );
+}
+
+#[test]
+fn value_refs_are_units() {
+    assert_eq!(
+        eval(&parser().parse(":a 256 (* $a 3)").unwrap()).into_vec(),
+        vec![Ok(Type::natural(256u16)), Ok(Type::natural(3u8))],
+    );
+}
+
+#[test]
+fn derefs_are_dependent() {
+    let [_, _, Ok(image)] = &*eval(
+        &parser()
+            .parse(":width 256 :height 256 :image (^ (* 256 256 256) @width @height)")
+            .unwrap(),
+    ) else {
+        panic!("expected an image type");
+    };
+
+    assert_eq!(image.states, None);
+}
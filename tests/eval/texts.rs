@@ -0,0 +1,72 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{ExprVariant, Scope},
+    eval::{eval, Error, Type},
+    natural::Natural,
+    parser::parser,
+    symbolic::Symbolic,
+    text::Encoding,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn utf8_text() {
+    let text = Type::text(Encoding::Utf8.encode("ab").unwrap());
+    assert_eq!(text.states, Some(Symbolic::from(Natural::ByteAligned(2))));
+    assert_eq!(
+        eval(&parser().parse("\"ab\"").unwrap()).into_vec(),
+        vec![Ok(text)],
+    );
+}
+
+#[test]
+fn text_in_encoding_context() {
+    assert_eq!(
+        eval(&parser().parse("(utf-16 \"ab\")").unwrap()).into_vec(),
+        vec![Ok(Type::text(Encoding::Utf16.encode("ab").unwrap()))],
+    );
+}
+
+#[test]
+fn encoding_context_is_lexical() {
+    assert_eq!(
+        eval(&parser().parse(":a \"ab\" (utf-16 a)").unwrap()).into_vec(),
+        vec![
+            Ok(Type::text(Encoding::Utf8.encode("ab").unwrap())),
+            Ok(Type::text(Encoding::Utf8.encode("ab").unwrap())),
+        ],
+    );
+}
+
+#[test]
+fn unencodable_text() {
+    assert_eq!(
+        eval(&parser().parse("(ascii-7 \"é\")").unwrap()).into_vec(),
+        vec![Err(Error::unencodable(9..12, 'é'))],
+    );
+}
+
+#[test]
+fn shared_scope_has_its_own_encoding() {
+    // Scopes are cheap to clone, so the same expressions can end up in
+    // different encoding contexts
+    let scope = parser().parse(r#"(ascii-7 ("é"))"#).unwrap();
+    let ExprVariant::SExpr(encoded) = &scope.exprs()[0].base.variant else {
+        panic!("expected a symbolic expression");
+    };
+
+    let shared = encoded.exprs()[1].clone();
+    let encoded = scope.exprs()[0].clone();
+    drop(scope);
+
+    let scope = Scope::try_from_exprs([encoded, shared]).unwrap();
+    assert_eq!(
+        eval(&scope).into_vec(),
+        vec![
+            Err(Error::unencodable(10..13, 'é')),
+            Err(Error::unknown_function(10..13)),
+        ],
+    );
+}
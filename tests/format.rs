@@ -0,0 +1,4 @@
+mod format {
+    #[rustfmt::skip] mod display;
+    #[rustfmt::skip] mod source;
+}
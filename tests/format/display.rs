@@ -0,0 +1,215 @@
+use chumsky::Parser;
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Scope},
+    format::{self, Config},
+    natural::Natural,
+    parser::parser,
+};
+
+fn parsed(src: &str) -> Scope {
+    let (scope, _) = parser().parse_recovery(src);
+    scope.unwrap()
+}
+
+fn formatted(src: &str, width: usize) -> String {
+    let (scope, _) = parser().parse_recovery(src);
+    let config = Config {
+        width,
+        ..Config::default()
+    };
+
+    format::scope(&scope.unwrap()).pretty(&config)
+}
+
+/// Checks that formatting a parsed scope and parsing it again gives the
+/// same scope, no matter how narrow the lines are.
+fn round_trip(src: &str) {
+    for width in [0, 20, 80] {
+        let formatted = formatted(src, width);
+        assert!(
+            parsed(&formatted).eq_ignoring_spans(&parsed(src)),
+            "{formatted}"
+        );
+    }
+}
+
+#[test]
+fn readme_layout() {
+    let src = ":my-image-format (* :width byte :height byte :image (^ :pixel (* :r byte :g byte :b byte) @width @height))";
+    assert_eq!(
+        formatted(src, 40),
+        "\
+:my-image-format
+(*
+  :width byte
+  :height byte
+  :image
+  (^
+    :pixel (* :r byte :g byte :b byte)
+    @width
+    @height
+  )
+)"
+    );
+
+    round_trip(src);
+}
+
+#[test]
+fn one_expr_per_line() {
+    let (scope, _) = parser().parse_recovery(":a 1   :b (+ a   a)\n\n\"text\"");
+    assert_eq!(scope.unwrap().to_string(), ":a 1\n:b (+ a a)\n\"text\"");
+}
+
+#[test]
+fn naturals() {
+    assert_eq!(
+        formatted("0b101 0o17 0xFF 1_000 2^32", 80),
+        "0b101\n0o17\n0xFF\n1_000\n2^32"
+    );
+    assert_eq!(formatted("2^64 0x100^100", 80), "2^64\n0x100^100");
+    round_trip("0 1 0xFF 2^63 2^64 3^100 256^1000");
+}
+
+#[test]
+fn naturals_keep_their_radix() {
+    for src in ["0xFF_FF", "0b1010"] {
+        let scope = parsed(src);
+        assert_eq!(scope.to_string(), src);
+        assert_eq!(scope.exprs()[0].to_string(), src);
+        assert_eq!(format::scope(&scope).pretty(&Config::default()), src);
+        assert_eq!(parsed(&scope.to_string()), scope);
+    }
+}
+
+#[test]
+fn built_naturals_are_decimal() {
+    let scope = Scope::try_from_exprs([
+        Expr::natural([], 0..0, 0xFFu8),
+        Expr::natural([], 0..0, Natural::BitAligned(64)),
+    ])
+    .unwrap();
+
+    assert_eq!(scope.to_string(), "255\n2^64");
+}
+
+#[test]
+fn symbols() {
+    assert_eq!(
+        formatted(":'a b' 1 :'(x)' 2 :'it''s' 3 :'1st' 4 '1st'", 80),
+        ":'a b' 1\n:'(x)' 2\n:'it''s' 3\n:'1st' 4\n'1st'"
+    );
+
+    round_trip(":'a b' 1 :'(x)' 2 :'it''s' 3 :'1st' 4 (+ '(x)' 'it''s' '1st' 'a doc')");
+}
+
+#[test]
+fn texts() {
+    assert_eq!(
+        formatted("\"a \"\"quoted\"\" text\"", 80),
+        "\"a \"\"quoted\"\" text\""
+    );
+    round_trip("\"a \"\"quoted\"\"\ntext\" \"\"");
+}
+
+#[test]
+fn references() {
+    assert_eq!(
+        formatted(":a (* :x (* :y 3)) :b a:x:y :c (* :a $a:x :d \\a:x @a)", 80),
+        ":a (* :x (* :y 3))\n:b a:x:y\n:c (* :a $a:x :d \\a:x @a)"
+    );
+
+    round_trip(":node (* :value 256 :node (? \\node)) :list (* :a :b node:value (+ \\\\a a))");
+}
+
+#[test]
+fn builtin_references() {
+    assert_eq!(
+        formatted(":+ 1 (* + \\+ utf-8 ascii-8)", 80),
+        ":+ 1\n(* + \\+ utf-8 ascii)"
+    );
+    round_trip(":+ 1 (* :* 2 (+ + * \\+ \\* \\\\+ utf-8 ascii-8))");
+}
+
+#[test]
+fn unresolved_references() {
+    let (scope, _) = parser().parse_recovery("(+ missing \\\\x:a:'b c')");
+    assert_eq!(scope.unwrap().to_string(), "(+ missing \\\\x:a:'b c')");
+}
+
+#[test]
+fn paths_into_sexprs() {
+    assert_eq!(formatted("(* :x (* :y 3)):x:y", 80), "3");
+    round_trip(":a (* :x 3 :y 4):y :b (* :x (* :y 3)):x:y");
+}
+
+#[test]
+fn nested_breaks() {
+    let src = ":a (* :b (+ 1 2 3 4 5 6) :c (| 0 1) @(^ 256 4))";
+    assert_eq!(
+        formatted(src, 20),
+        "\
+:a
+(*
+  :b (+ 1 2 3 4 5 6)
+  :c (| 0 1)
+  @(^ 256 4)
+)"
+    );
+
+    assert_eq!(
+        formatted(src, 10),
+        "\
+:a
+(*
+  :b
+  (+
+    1
+    2
+    3
+    4
+    5
+    6
+  )
+  :c
+  (| 0 1)
+  @(^
+    256
+    4
+  )
+)"
+    );
+
+    round_trip(src);
+}
+
+#[test]
+fn empty() {
+    assert_eq!(formatted("", 80), "");
+    assert_eq!(formatted("()", 80), "()");
+    round_trip("(* () (()))");
+}
+
+#[test]
+fn exprs_on_their_own() {
+    let (scope, _) = parser().parse_recovery(":a 1 :b (* :x a :y (+ x 2))");
+    let scope = scope.unwrap();
+    assert_eq!(
+        scope.exprs()[1].to_string(),
+        ":b (* :x '<unnamed>' :y (+ x 2))"
+    );
+    assert_eq!(
+        format::expr(&scope.exprs()[0]).pretty(&Config::default()),
+        ":a 1"
+    );
+}
+
+#[test]
+fn round_trip_ignores_spans_only() {
+    assert!(parsed(":a (+ 1 a:b)").eq_ignoring_spans(&parsed(":a  (+ 1\n a:b)")));
+    assert!(!parsed(":a (+ 1 a:b)").eq_ignoring_spans(&parsed(":a (+ 2 a:b)")));
+    assert!(!parsed(":a (+ 1 a:b)").eq_ignoring_spans(&parsed(":a (+ 1 a:c)")));
+    assert!(!parsed(":a 1").eq_ignoring_spans(&parsed(":b 1")));
+}
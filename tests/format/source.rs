@@ -0,0 +1,103 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    cst::Cst,
+    format::{self, Config},
+};
+
+fn formatted(src: &str, width: usize) -> String {
+    let config = Config {
+        width,
+        ..Config::default()
+    };
+
+    format::source(&Cst::parse(src)).pretty(&config)
+}
+
+/// Checks that only whitespace changes, and that formatting again
+/// changes nothing.
+fn assert_formatted(src: &str, width: usize, expected: &str) {
+    let formatted = formatted(src, width);
+    assert_eq!(formatted, expected);
+    let strip = |src: &str| src.split_whitespace().collect::<String>();
+    assert_eq!(strip(&formatted), strip(src));
+    assert_eq!(self::formatted(&formatted, width), formatted);
+}
+
+#[test]
+fn keeps_tokens() {
+    assert_formatted(
+        ":a   0xFF_FF  :b 2^32\n:'c doc'  \"some  text\"  \\\\a:x",
+        80,
+        ":a 0xFF_FF\n:b 2^32\n:'c doc' \"some  text\"\n\\\\a:x\n",
+    );
+}
+
+#[test]
+fn readme_layout() {
+    assert_formatted(
+        ":my-image-format (* :width byte :height byte\n:image (^ :pixel (* :r byte :g byte :b byte) @width @height))",
+        40,
+        "\
+:my-image-format
+(*
+  :width byte
+  :height byte
+  :image
+  (^
+    :pixel (* :r byte :g byte :b byte)
+    @width
+    @height
+  )
+)
+",
+    );
+}
+
+#[test]
+fn comments() {
+    assert_formatted(
+        "; leading\n:a 1 ; trailing\n:b (+ 1 ; one\n  2 #| two |# 3) :c #| block |# 2",
+        80,
+        "\
+; leading
+:a 1 ; trailing
+:b
+(+
+  1 ; one
+  2 #| two |# 3
+)
+:c #| block |# 2
+",
+    );
+}
+
+#[test]
+fn trailing_line_comment_in_list() {
+    assert_formatted("(+ 1 2 ; end\n)", 80, "(+\n  1\n  2 ; end\n)\n");
+}
+
+#[test]
+fn blank_lines() {
+    assert_formatted(
+        "\n\n:a 1\n\n\n\n:b 2\n:c (+ 1\n\n 2)\n\n",
+        80,
+        ":a 1\n\n:b 2\n:c\n(+\n  1\n\n  2\n)\n",
+    );
+}
+
+#[test]
+fn adjacent_tokens() {
+    assert_formatted(
+        ":a(* :x 1):x  @(+ 1 2)  $a  :b:c 3",
+        80,
+        ":a(* :x 1):x\n@(+ 1 2)\n$a\n:b:c 3\n",
+    );
+}
+
+#[test]
+fn empty() {
+    assert_formatted("", 80, "");
+    assert_formatted(" \n\n ", 80, "");
+    assert_formatted("( )", 80, "()\n");
+}
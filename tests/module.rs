@@ -0,0 +1,3 @@
+mod module {
+    #[rustfmt::skip] mod imports;
+}
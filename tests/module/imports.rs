@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, ExprVariant, Label},
+    eval::eval,
+    module::Loader,
+    parser::{parser, Error},
+    source::FileId,
+};
+
+use chumsky::Parser;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/module/fixtures")
+        .join(name)
+}
+
+fn errors(loader: &Loader) -> Vec<(String, Error)> {
+    loader
+        .errors()
+        .map(|err| {
+            let path = &loader.sources().get(err.file).path;
+            let name = path.file_name().unwrap().to_string_lossy();
+            // Compare files by name rather than by load order
+            (name.into_owned(), err.clone().in_file(FileId::default()))
+        })
+        .collect()
+}
+
+#[test]
+fn import_labelled_scope() {
+    let mut loader = Loader::new();
+    let main = loader.load(fixture("main.ari")).unwrap();
+    assert_eq!(errors(&loader), []);
+
+    let scope = loader.file(main).scope.clone().unwrap();
+    assert_eq!(
+        scope.exprs()[2],
+        Expr::resolved_reference([Label::new(46..53, "header")], 54..68, 0, -2, [0, 2])
+    );
+
+    let single_file = parser().parse(":header (* :width 32 :height 32)").unwrap();
+
+    assert_eq!(eval(&scope)[2], eval(&single_file)[0]);
+}
+
+#[test]
+fn load_each_file_once() {
+    let mut loader = Loader::new();
+    let main = loader.load(fixture("main.ari")).unwrap();
+    assert_eq!(loader.load(fixture("main.ari")).unwrap(), main);
+    assert_eq!(loader.sources().files().count(), 2);
+
+    let scope = loader.file(main).scope.clone().unwrap();
+    assert_eq!(scope.exprs()[0].base.variant, scope.exprs()[1].base.variant);
+}
+
+#[test]
+fn spans_carry_file_ids() {
+    let mut loader = Loader::new();
+    let main = loader.load(fixture("main.ari")).unwrap();
+    let png = loader.load(fixture("png.ari")).unwrap();
+    assert_ne!(main, png);
+
+    let scope = loader.file(main).scope.clone().unwrap();
+    let ExprVariant::SExpr(imported) = &scope.exprs()[0].base.variant else {
+        panic!("import wasn't replaced with a scope");
+    };
+
+    assert_eq!(scope.exprs()[0].base.file, main);
+    assert_eq!(imported.exprs()[0].base.file, png);
+}
+
+#[test]
+fn eval_errors_carry_file_ids() {
+    let mut loader = Loader::new();
+    let main = loader.load(fixture("bottom_main.ari")).unwrap();
+    let bottom = loader.load(fixture("bottom.ari")).unwrap();
+    assert_eq!(errors(&loader), []);
+
+    let scope = loader.file(main).scope.clone().unwrap();
+    assert_eq!(
+        eval(&scope)[1],
+        Err(ari::eval::Error::bottom(12..15).in_file(bottom))
+    );
+}
+
+#[test]
+#[allow(clippy::single_range_in_vec_init)]
+fn import_reports_errors_once() {
+    let mut loader = Loader::new();
+    loader.load(fixture("alias_main.ari")).unwrap();
+    assert_eq!(
+        errors(&loader),
+        [("alias.ari".into(), Error::unguarded_cycle(3..4, [8..9]))]
+    );
+}
+
+#[test]
+fn import_from_search_path() {
+    let mut loader = Loader::new();
+    assert!(loader.load(fixture("search.ari")).is_ok());
+    assert_eq!(
+        errors(&loader),
+        [
+            ("search.ari".into(), Error::import_not_found(14..20)),
+            ("search.ari".into(), Error::invalid_path(32..37)),
+        ],
+        "failed imports are empty"
+    );
+
+    let mut loader = Loader::new().with_search_path(fixture("lib"));
+    let search = loader.load(fixture("search.ari")).unwrap();
+    assert_eq!(errors(&loader), []);
+
+    let scope = loader.file(search).scope.clone().unwrap();
+    assert_eq!(eval(&scope)[1], eval(&parser().parse("256").unwrap())[0]);
+}
+
+#[test]
+fn import_cycle() {
+    let mut loader = Loader::new();
+    loader.load(fixture("cycle_a.ari")).unwrap();
+    assert_eq!(
+        errors(&loader),
+        [("cycle_b.ari".into(), Error::import_cycle(11..20))]
+    );
+}
+
+#[test]
+fn import_not_found() {
+    let mut loader = Loader::new();
+    loader.load(fixture("missing.ari")).unwrap();
+    assert_eq!(
+        errors(&loader),
+        [("missing.ari".into(), Error::import_not_found(11..25))]
+    );
+}
+
+#[test]
+fn load_source() {
+    let mut loader = Loader::new();
+    let id = loader.load_source(
+        fixture("<stdin>"),
+        r#":png (import "png") :length png:chunk:length"#,
+    );
+    assert_eq!(errors(&loader), []);
+    assert_eq!(
+        loader.file(id).scope.clone().unwrap().exprs()[1],
+        Expr::resolved_reference([Label::new(20..27, "length")], 28..44, 0, -1, [0, 1])
+    );
+}
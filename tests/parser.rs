@@ -1,8 +1,18 @@
 mod parser {
+    #[rustfmt::skip] mod builtins;
+    #[rustfmt::skip] mod comments;
+    #[rustfmt::skip] mod cycles;
+    #[rustfmt::skip] mod derefs;
+    #[rustfmt::skip] mod expected;
+    #[rustfmt::skip] mod extended_symbols;
     #[rustfmt::skip] mod labels;
     #[rustfmt::skip] mod naturals;
+    #[rustfmt::skip] mod parent_references;
+    #[rustfmt::skip] mod recovery;
     #[rustfmt::skip] mod resolved_references;
     #[rustfmt::skip] mod sexprs;
     #[rustfmt::skip] mod symbols;
+    #[rustfmt::skip] mod texts;
     #[rustfmt::skip] mod unresolved_references;
+    #[rustfmt::skip] mod value_refs;
 }
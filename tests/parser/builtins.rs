@@ -0,0 +1,103 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Label, Scope, Symbol},
+    parser::{parser, Error},
+    prelude::Builtin,
+    text::Encoding,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn builtin_in_nested_sexpr() {
+    assert_eq!(
+        parser().parse_recovery("(+ 1 (* 2 3))"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..13,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Add),
+                        Expr::natural([], 3..4, 1u8),
+                        Expr::sexpr(
+                            [],
+                            5..12,
+                            Scope::try_from_exprs([
+                                Expr::builtin_reference([], 6..7, 3, Builtin::Multiply),
+                                Expr::natural([], 8..9, 2u8),
+                                Expr::natural([], 10..11, 3u8),
+                            ])
+                            .unwrap()
+                        ),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn builtin_with_multiple_names() {
+    assert_eq!(
+        parser().parse_recovery("ascii ascii-8"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::builtin_reference([], 0..5, 1, Builtin::Encoding(Encoding::Ascii8)),
+                    Expr::builtin_reference([], 6..13, 1, Builtin::Encoding(Encoding::Ascii8)),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn labels_shadow_builtins() {
+    assert_eq!(
+        parser().parse_recovery(":+ 256 (+ 1)"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..2, "+")], 3..6, 256u16),
+                    Expr::sexpr(
+                        [],
+                        7..12,
+                        Scope::try_from_exprs([
+                            Expr::resolved_reference([], 8..9, 1, -1, []),
+                            Expr::natural([], 10..11, 1u8),
+                        ])
+                        .unwrap()
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn cant_apply_path_to_builtin() {
+    assert_eq!(
+        parser().parse_recovery("+:x"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::unresolved_reference(
+                    [],
+                    0..3,
+                    Symbol::new(0..1, "+"),
+                    [Label::new(1..3, "x")],
+                )])
+                .unwrap()
+            ),
+            vec![Error::invalid_path(1..3)],
+        )
+    );
+}
@@ -0,0 +1,107 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Label, Scope},
+    parser::{open_parser, parser, Error, Expected},
+};
+
+use chumsky::Parser;
+
+#[test]
+fn line_comment() {
+    assert_eq!(
+        open_parser().parse_recovery("; about x\nx ; after x"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 10..11, "x")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn block_comment() {
+    assert_eq!(
+        open_parser().parse_recovery("x #| between |# y"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::unresolved_symbol([], 0..1, "x"),
+                    Expr::unresolved_symbol([], 16..17, "y"),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn nested_block_comment() {
+    assert_eq!(
+        open_parser().parse_recovery("#| outer #| inner |# |# x"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 24..25, "x")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn separates_exprs() {
+    assert_eq!(
+        open_parser().parse_recovery("x;comment\ny#||#z"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::unresolved_symbol([], 0..1, "x"),
+                    Expr::unresolved_symbol([], 10..16, "y#||#z"),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn between_labels_and_expr() {
+    assert_eq!(
+        parser().parse_recovery(":a ; first\n:b #| second |# 256"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::natural(
+                    [Label::new(0..2, "a"), Label::new(11..13, "b")],
+                    27..30,
+                    256u16
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn in_sexpr() {
+    assert_eq!(parser().parse_recovery("(+ ; one\n 1 #| two |#)").1, vec![]);
+}
+
+#[test]
+fn unterminated_block_comment_message() {
+    let (_, errors) = parser().parse_recovery("#| never closed");
+    assert_eq!(
+        errors.iter().map(|err| err.to_string()).collect::<Vec<_>>(),
+        ["expected `|#`, found the end of input"]
+    );
+}
+
+#[test]
+fn unterminated_block_comment() {
+    assert_eq!(
+        open_parser().parse_recovery("x #| never closed"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..1, "x")]).unwrap()),
+            vec![Error::unexpected_end(17).expecting(Expected::CommentEnd)],
+        )
+    );
+}
@@ -0,0 +1,166 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Scope},
+    cycles::{cycles, Cycle},
+    parser::{parser, Error},
+    source::FileId,
+};
+
+use chumsky::Parser;
+
+fn parse_cycles(src: &str) -> Box<[Cycle]> {
+    cycles(&parser().parse_recovery(src).0.unwrap())
+}
+
+#[test]
+fn self_reference_is_not_a_cycle() {
+    assert_eq!(parse_cycles(":a a"), [].into());
+}
+
+#[test]
+fn guarded_recursion() {
+    assert_eq!(
+        parser()
+            .parse_recovery(":byte 256 :list (+ 1 (* byte list))")
+            .1,
+        vec![]
+    );
+
+    assert_eq!(
+        parse_cycles(":byte 256 :list (+ 1 (* byte list))"),
+        [Cycle {
+            file: FileId::default(),
+            references: [29..33].into(),
+            guarded: true,
+        }]
+        .into()
+    );
+}
+
+#[test]
+fn guarded_by_deref() {
+    assert_eq!(
+        parse_cycles(":a @a"),
+        [Cycle {
+            file: FileId::default(),
+            references: [4..5].into(),
+            guarded: true,
+        }]
+        .into()
+    );
+}
+
+#[test]
+fn guarded_through_alias() {
+    assert_eq!(
+        parse_cycles(":a (+ 1 b) :b a"),
+        [Cycle {
+            file: FileId::default(),
+            references: [8..9, 14..15].into(),
+            guarded: true,
+        }]
+        .into()
+    );
+}
+
+#[test]
+fn unguarded_product() {
+    assert_eq!(
+        parser().parse_recovery(":a (* 256 a)").1,
+        vec![Error::unguarded_cycle(10..11, [])]
+    );
+
+    assert_eq!(
+        parser().parse_recovery(":a (* 2 b) :b (^ a 2)").1,
+        vec![Error::unguarded_cycle(8..9, [17..18])]
+    );
+}
+
+#[test]
+fn unguarded_bottom() {
+    assert_eq!(
+        parser().parse_recovery(":a (+ 0 a)").1,
+        vec![Error::unguarded_cycle(8..9, [])]
+    );
+
+    assert_eq!(
+        parser().parse_recovery(":a (+ (+) (* 2 a))").1,
+        vec![Error::unguarded_cycle(15..16, [])]
+    );
+}
+
+#[test]
+fn guarded_by_other_alternative() {
+    assert_eq!(
+        parse_cycles(":a (+ 0 b (* 2 a)) :b 1"),
+        [Cycle {
+            file: FileId::default(),
+            references: [15..16].into(),
+            guarded: true,
+        }]
+        .into()
+    );
+}
+
+#[test]
+fn unguarded_alias() {
+    assert_eq!(
+        parse_cycles(":a b :b c :c a"),
+        [Cycle {
+            file: FileId::default(),
+            references: [3..4, 8..9, 13..14].into(),
+            guarded: false,
+        }]
+        .into()
+    );
+
+    assert_eq!(
+        parser().parse_recovery(":a b :b c :c a").1,
+        vec![Error::unguarded_cycle(3..4, [8..9, 13..14])]
+    );
+}
+
+#[test]
+fn unguarded_head() {
+    assert_eq!(
+        parser().parse_recovery(":f (f 1)").1,
+        vec![Error::unguarded_cycle(4..5, [])]
+    );
+}
+
+#[test]
+fn unguarded_path() {
+    assert_eq!(
+        parser().parse_recovery(":a (+ :x b) :b a:x").1,
+        vec![Error::unguarded_cycle(9..10, [15..18])]
+    );
+}
+
+#[test]
+fn unguarded_within_guarded() {
+    assert_eq!(
+        parse_cycles(":a (+ 1 b) :b c :c b"),
+        [Cycle {
+            file: FileId::default(),
+            references: [14..15, 19..20].into(),
+            guarded: false,
+        }]
+        .into()
+    );
+}
+
+#[test]
+fn shared_scope_is_not_a_cycle() {
+    let inner = Scope::try_from_exprs([Expr::resolved_reference([], 10..11, 1, -1, [])]).unwrap();
+    let root = Scope::try_from_exprs([
+        Expr::natural([], 0..1, 1u8),
+        Expr::sexpr([], 2..5, inner.clone()),
+        Expr::sexpr([], 6..9, inner),
+    ])
+    .unwrap();
+
+    assert_eq!(cycles(&root), [].into());
+}
@@ -0,0 +1,205 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Label, Scope, Symbol},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn deref_symbol() {
+    assert_eq!(
+        open_parser().parse_recovery("@width"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::deref(
+                    [],
+                    0..6,
+                    Expr::unresolved_symbol([], 1..6, "width").base,
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn deref_natural() {
+    assert_eq!(
+        parser().parse_recovery("@123"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::deref([], 0..4, Expr::natural([], 1..4, 123u8).base,)
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn deref_sexpr() {
+    assert_eq!(
+        parser().parse_recovery("@(+ :small 1 :large 1)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::deref(
+                    [],
+                    0..22,
+                    Expr::sexpr(
+                        [],
+                        1..22,
+                        Scope::try_from_exprs([
+                            Expr::builtin_reference([], 2..3, 2, Builtin::Add),
+                            Expr::natural([Label::new(4..10, "small")], 11..12, 1u8),
+                            Expr::natural([Label::new(13..19, "large")], 20..21, 1u8),
+                        ])
+                        .unwrap()
+                    )
+                    .base,
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn deref_path() {
+    assert_eq!(
+        open_parser().parse_recovery("@symbol:x"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::deref(
+                    [],
+                    0..9,
+                    Expr::unresolved_reference(
+                        [],
+                        1..9,
+                        Symbol::new(1..7, "symbol"),
+                        [Label::new(7..9, "x")]
+                    )
+                    .base,
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn nested_deref() {
+    assert_eq!(
+        open_parser().parse_recovery("@@a"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::deref(
+                    [],
+                    0..3,
+                    Expr::deref([], 1..3, Expr::unresolved_symbol([], 2..3, "a").base).base,
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn labelled_deref() {
+    assert_eq!(
+        parser().parse_recovery(":a 256 :b @a"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..2, "a")], 3..6, 256u16),
+                    Expr::deref(
+                        [Label::new(7..9, "b")],
+                        10..12,
+                        Expr::resolved_reference([], 11..12, 0, -1, []).base,
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn deref_in_parent_scope() {
+    assert_eq!(
+        parser().parse_recovery(":width 256 (^ 256 @width)"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..6, "width")], 7..10, 256u16),
+                    Expr::sexpr(
+                        [],
+                        11..25,
+                        Scope::try_from_exprs([
+                            Expr::builtin_reference([], 12..13, 2, Builtin::Exponentiate),
+                            Expr::natural([], 14..17, 256u16),
+                            Expr::deref(
+                                [],
+                                18..24,
+                                Expr::resolved_reference([], 19..24, 1, -1, []).base,
+                            ),
+                        ])
+                        .unwrap()
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn must_have_expr() {
+    assert_eq!(
+        parser().parse_recovery("@"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![Error::unexpected_end(1)
+                .expecting(Expected::Expr)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::Deref)],
+        )
+    );
+}
+
+#[test]
+fn cant_have_whitespace() {
+    assert_eq!(
+        open_parser().parse_recovery("@ a"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 2..3, "a")]).unwrap()),
+            vec![Error::unexpected_char(1..2, ' ')
+                .expecting(Expected::Expr)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::Deref)],
+        )
+    );
+}
+
+#[test]
+fn symbols_cant_have_at() {
+    assert_eq!(
+        open_parser().parse_recovery("a@b"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..1, "a")]).unwrap()),
+            vec![Error::trailing_garbage(1..3)],
+        )
+    );
+}
@@ -0,0 +1,62 @@
+use pretty_assertions::assert_eq;
+
+use ari::parser::{parser, Error, Expected};
+
+use chumsky::Parser;
+
+fn errors(src: &str) -> Vec<Error> {
+    parser().parse_recovery(src).1
+}
+
+#[test]
+fn expected_expr() {
+    let errors = errors(":a (+ 1 @) :b 3");
+    assert_eq!(errors[0].expected(), [Expected::Expr]);
+    assert_eq!(errors[0].to_string(), "expected an expression, found `)`");
+}
+
+#[test]
+fn expected_label_name() {
+    let errors = errors("(+ 1 :)");
+    assert_eq!(
+        errors[0].expected(),
+        [Expected::Char('\''), Expected::Symbol]
+    );
+
+    assert_eq!(errors[0].to_string(), "expected `'` or a symbol, found `)`");
+}
+
+#[test]
+fn expected_right_paren() {
+    let errors = errors("(+ 1 0x) :b 3");
+    assert_eq!(
+        errors[0].expected(),
+        [
+            Expected::Char(')'),
+            Expected::Char(':'),
+            Expected::Char('^')
+        ]
+    );
+
+    assert_eq!(errors[0].to_string(), "expected `)`, `:` or `^`, found `x`");
+}
+
+#[test]
+fn expected_at_end() {
+    assert_eq!(
+        errors("(")[0].to_string(),
+        "expected `)`, found the end of input"
+    );
+
+    assert_eq!(
+        errors(":label")[0].to_string(),
+        "expected an expression, found the end of input"
+    );
+}
+
+#[test]
+fn expected_only_for_unexpected_chars() {
+    let errors = errors("1_");
+    assert_eq!(errors[0].expected(), []);
+    assert_eq!(errors[0].to_string(), "invalid digit separator");
+}
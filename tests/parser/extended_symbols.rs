@@ -0,0 +1,208 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Label, Scope, Symbol},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn special_chars() {
+    assert_eq!(
+        open_parser().parse_recovery("'(symbol)'"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..10, "(symbol)")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn escaped_quotes() {
+    assert_eq!(
+        open_parser().parse_recovery("'''quoted'''"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..12, "'quoted'")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn escaped_quote_in_middle() {
+    assert_eq!(
+        open_parser().parse_recovery("'don''t'"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..8, "don't")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn documented_label() {
+    assert_eq!(
+        parser().parse_recovery(":'pixel A 24bit RGB pixel' (* :r 256 :g 256 :b 256)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [Label::new(0..26, "pixel").with_doc("A 24bit RGB pixel")],
+                    27..51,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 28..29, 2, Builtin::Multiply),
+                        Expr::natural([Label::new(30..32, "r")], 33..36, 256u16),
+                        Expr::natural([Label::new(37..39, "g")], 40..43, 256u16),
+                        Expr::natural([Label::new(44..46, "b")], 47..50, 256u16),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn documented_symbol() {
+    assert_eq!(
+        open_parser().parse_recovery("'pixel Here we''re referencing pixel'"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::unresolved_reference(
+                    [],
+                    0..37,
+                    Symbol::new(0..37, "pixel").with_doc("Here we're referencing pixel"),
+                    [],
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn resolves_documented_label() {
+    assert_eq!(
+        parser().parse_recovery(":'a first' 256 'a second'"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..10, "a").with_doc("first")], 11..14, 256u16),
+                    Expr::resolved_reference([], 15..25, 0, -1, []),
+                ])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn in_path() {
+    assert_eq!(
+        open_parser().parse_recovery("symbol:'x:y'"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::unresolved_reference(
+                    [],
+                    0..12,
+                    Symbol::new(0..6, "symbol"),
+                    [Label::new(6..12, "x:y")],
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn cant_be_empty() {
+    assert_eq!(
+        open_parser().parse_recovery("''"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..2, "")]).unwrap()),
+            vec![Error::empty_symbol(0..2)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn cant_be_only_documentation() {
+    assert_eq!(
+        open_parser().parse_recovery("' doc'"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::unresolved_reference(
+                    [],
+                    0..6,
+                    Symbol::new(0..6, "").with_doc("doc"),
+                    [],
+                )])
+                .unwrap()
+            ),
+            vec![Error::empty_symbol(0..6)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn must_be_terminated() {
+    assert_eq!(
+        open_parser().parse_recovery("'abc"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![Error::unexpected_end(4)
+                .expecting(Expected::Char('\''))
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn must_be_terminated_by_odd_quotes() {
+    assert_eq!(
+        open_parser().parse_recovery("'abc''"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![Error::unexpected_end(6)
+                .expecting(Expected::Char('\''))
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn unterminated_is_reported_once() {
+    let unterminated = |end| {
+        Error::unexpected_end(end)
+            .expecting(Expected::Char('\''))
+            .with_label(ErrorLabel::Symbol)
+            .with_label(ErrorLabel::Reference)
+    };
+
+    assert_eq!(
+        parser().parse_recovery("'a"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![unterminated(2)]
+        )
+    );
+    assert_eq!(
+        parser().parse_recovery(":b 'a"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![unterminated(5).with_label(ErrorLabel::LabelledExpr)]
+        )
+    );
+}
@@ -2,7 +2,7 @@ use pretty_assertions::assert_eq;
 
 use ari::{
     ast::{Expr, Label, Scope, ScopeError},
-    parser::{parser, Error, ErrorLabel},
+    parser::{parser, Error, ErrorLabel, Expected},
 };
 
 use chumsky::Parser;
@@ -69,10 +69,14 @@ fn names_cant_have_colon() {
             Some(Scope::try_from_exprs([Expr::natural([], 3..6, 256u16)]).unwrap()),
             vec![
                 Error::unexpected_char(1..2, ':')
+                    .expecting(Expected::Char('\''))
+                    .expecting(Expected::Symbol)
                     .with_label(ErrorLabel::Symbol)
                     .with_label(ErrorLabel::Label)
                     .with_label(ErrorLabel::LabelledExpr),
                 Error::unexpected_char(2..3, ' ')
+                    .expecting(Expected::Char('\''))
+                    .expecting(Expected::Symbol)
                     .with_label(ErrorLabel::Symbol)
                     .with_label(ErrorLabel::Label)
                     .with_label(ErrorLabel::LabelledExpr),
@@ -96,10 +100,13 @@ fn names_cant_have_left_paren() {
             ),
             vec![
                 Error::unexpected_char(1..2, '(')
+                    .expecting(Expected::Char('\''))
+                    .expecting(Expected::Symbol)
                     .with_label(ErrorLabel::Symbol)
                     .with_label(ErrorLabel::Label)
                     .with_label(ErrorLabel::LabelledExpr),
                 Error::unexpected_end(6)
+                    .expecting(Expected::Char(')'))
                     .with_label(ErrorLabel::SExpr)
                     .with_label(ErrorLabel::Reference)
                     .with_label(ErrorLabel::LabelledExpr)
@@ -113,14 +120,13 @@ fn names_cant_have_right_paren() {
     assert_eq!(
         parser().parse_recovery(":) 256"),
         (
-            Some(Scope::try_from_exprs([]).unwrap()),
-            vec![
-                Error::unexpected_char(1..2, ')')
-                    .with_label(ErrorLabel::Symbol)
-                    .with_label(ErrorLabel::Label)
-                    .with_label(ErrorLabel::LabelledExpr),
-                Error::trailing_garbage(1..6),
-            ],
+            Some(Scope::try_from_exprs([Expr::natural([], 3..6, 256u16)]).unwrap()),
+            vec![Error::unexpected_char(1..2, ')')
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Label)
+                .with_label(ErrorLabel::LabelledExpr)],
         )
     );
 }
@@ -132,6 +138,8 @@ fn must_have_name() {
         (
             Some(Scope::try_from_exprs([]).unwrap()),
             vec![Error::unexpected_char(1..2, ' ')
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
                 .with_label(ErrorLabel::Symbol)
                 .with_label(ErrorLabel::Label)
                 .with_label(ErrorLabel::LabelledExpr)],
@@ -149,6 +157,8 @@ fn must_have_name_in_sexpr() {
                     .unwrap()
             ),
             vec![Error::unexpected_char(2..3, ' ')
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
                 .with_label(ErrorLabel::Symbol)
                 .with_label(ErrorLabel::Label)
                 .with_label(ErrorLabel::LabelledExpr)
@@ -164,7 +174,9 @@ fn must_have_associated_expr() {
         parser().parse_recovery(":label "),
         (
             Some(Scope::try_from_exprs([]).unwrap()),
-            vec![Error::unexpected_end(7).with_label(ErrorLabel::LabelledExpr)]
+            vec![Error::unexpected_end(7)
+                .expecting(Expected::Expr)
+                .with_label(ErrorLabel::LabelledExpr)]
         )
     );
 }
@@ -179,6 +191,7 @@ fn must_have_associated_expr_in_sexpr() {
                     .unwrap()
             ),
             vec![Error::unexpected_end(8)
+                .expecting(Expected::Expr)
                 .with_label(ErrorLabel::LabelledExpr)
                 .with_label(ErrorLabel::SExpr)
                 .with_label(ErrorLabel::Reference)]
@@ -4,7 +4,8 @@ use std::str::FromStr;
 
 use ari::{
     ast::{Expr, Scope},
-    parser::{parser, Error},
+    natural::Natural,
+    parser::{parser, Error, ErrorLabel},
 };
 
 use chumsky::Parser;
@@ -76,6 +77,197 @@ fn hexidecimal() {
     );
 }
 
+#[test]
+fn prefixed_with_leading_zeros() {
+    assert_eq!(
+        parser().parse_recovery("0x0100"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..6, 256u16)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn decimal_with_separators() {
+    assert_eq!(
+        parser().parse_recovery("1_000_000"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..9, 1_000_000u32)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn binary_with_separators() {
+    assert_eq!(
+        parser().parse_recovery("0b1_0000_0000"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..13, 256u16)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn octal_with_separators() {
+    assert_eq!(
+        parser().parse_recovery("0o7_777"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..7, 0o7777u16)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn hexidecimal_with_separators() {
+    assert_eq!(
+        parser().parse_recovery("0xFFFF_FFFF_0000_0000"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::natural([], 0..21, 0xFFFF_FFFF_0000_0000u64)])
+                    .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn cant_have_leading_separator() {
+    assert_eq!(
+        parser().parse_recovery("0x_FF"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..5, 0xFFu8)]).unwrap()),
+            vec![Error::invalid_separator(2..3)
+                .with_label(ErrorLabel::Natural)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn cant_have_trailing_separator() {
+    assert_eq!(
+        parser().parse_recovery("10_"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..3, 10u8)]).unwrap()),
+            vec![Error::invalid_separator(2..3)
+                .with_label(ErrorLabel::Natural)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn cant_have_double_separator() {
+    assert_eq!(
+        parser().parse_recovery("1__000"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..6, 1000u16)]).unwrap()),
+            vec![Error::invalid_separator(1..3)
+                .with_label(ErrorLabel::Natural)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn power() {
+    assert_eq!(
+        parser().parse_recovery("3^3"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..3, 27u8)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn power_of_two() {
+    assert_eq!(
+        parser().parse_recovery("2^32"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..4, 0x100000000u64)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn power_with_radix() {
+    assert_eq!(
+        parser().parse_recovery("0x100^0b100"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..11, 0x100000000u64)]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn supports_gigantic_powers_of_two() {
+    assert_eq!(
+        parser().parse_recovery("2^4096"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::natural([], 0..6, Natural::ByteAligned(512))])
+                    .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn power_must_fit_in_memory() {
+    assert_eq!(
+        parser().parse_recovery("2^99999999999999999999999"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..25, 0u8)]).unwrap()),
+            vec![Error::natural_overflow(0..25)
+                .with_label(ErrorLabel::Natural)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn unaligned_power_must_be_small() {
+    assert_eq!(
+        parser().parse_recovery("3^100000000"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..11, 0u8)]).unwrap()),
+            vec![Error::natural_overflow(0..11)
+                .with_label(ErrorLabel::Natural)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+    assert_eq!(
+        parser().parse_recovery("3^10000"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::natural([], 0..7, BigUint::from(3u8).pow(10000))])
+                    .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn power_must_have_exponent() {
+    assert_eq!(
+        parser().parse_recovery("2^"),
+        (
+            Some(Scope::try_from_exprs([Expr::natural([], 0..1, 2u8)]).unwrap()),
+            vec![Error::trailing_garbage(1..2)],
+        )
+    );
+}
+
 #[test]
 fn supports_big_naturals_that_fit_in_memory() {
     assert_eq!(
@@ -0,0 +1,128 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, ExprVariant, Label, Reference, Scope, Symbol},
+    parser::{parser, Error},
+    prelude::Builtin,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn skip_shadowing_label() {
+    assert_eq!(
+        parser().parse_recovery(r":node (* :value 256 :node (? \node))"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [Label::new(0..5, "node")],
+                    6..36,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 7..8, 2, Builtin::Multiply),
+                        Expr::natural([Label::new(9..15, "value")], 16..19, 256u16),
+                        Expr::sexpr(
+                            [Label::new(20..25, "node")],
+                            26..35,
+                            Scope::try_from_exprs([
+                                Expr::builtin_reference([], 27..28, 3, Builtin::ZeroOrOne),
+                                Expr::resolved_reference([], 29..34, 2, 0, []),
+                            ])
+                            .unwrap()
+                        ),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![]
+        ),
+    );
+}
+
+#[test]
+fn skip_multiple_labels() {
+    assert_eq!(
+        parser().parse_recovery(r":a 1 (:a 2 (:a 3 \\a))"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..2, "a")], 3..4, 1u8),
+                    Expr::sexpr(
+                        [],
+                        5..22,
+                        Scope::try_from_exprs([
+                            Expr::natural([Label::new(6..8, "a")], 9..10, 2u8),
+                            Expr::sexpr(
+                                [],
+                                11..21,
+                                Scope::try_from_exprs([
+                                    Expr::natural([Label::new(12..14, "a")], 15..16, 3u8),
+                                    Expr::resolved_reference([], 17..20, 2, -1, []),
+                                ])
+                                .unwrap()
+                            ),
+                        ])
+                        .unwrap()
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![]
+        ),
+    );
+}
+
+#[test]
+fn skip_with_path() {
+    assert_eq!(
+        parser().parse_recovery(r":x (:y 1) (:x 2 \x:y)"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::sexpr(
+                        [Label::new(0..2, "x")],
+                        3..9,
+                        Scope::try_from_exprs([Expr::natural([Label::new(4..6, "y")], 7..8, 1u8)])
+                            .unwrap()
+                    ),
+                    Expr::sexpr(
+                        [],
+                        10..21,
+                        Scope::try_from_exprs([
+                            Expr::natural([Label::new(11..13, "x")], 14..15, 2u8),
+                            Expr::resolved_reference([], 16..20, 1, -1, [0]),
+                        ])
+                        .unwrap()
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![]
+        ),
+    );
+}
+
+#[test]
+fn skip_every_label() {
+    assert_eq!(
+        parser().parse_recovery(r":a 1 \a"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..2, "a")], 3..4, 1u8),
+                    Expr::variant(
+                        [],
+                        5..7,
+                        ExprVariant::Reference(Reference::unresolved_skipping(
+                            Symbol::new(6..7, "a"),
+                            [],
+                            1
+                        ))
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![Error::unresolved_symbol(6..7, Some("a".into()))]
+        ),
+    );
+}
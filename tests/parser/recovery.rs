@@ -0,0 +1,156 @@
+use pretty_assertions::assert_eq;
+
+use ari::parser::{parser, Error, ErrorLabel, Expected};
+
+use chumsky::Parser;
+
+/// Parses a program with mistakes in it, returning the labels that
+/// still made it into the root scope along with the errors.
+fn recover(src: &str) -> (Vec<String>, Vec<Error>) {
+    let (scope, errors) = parser().parse_recovery(src);
+    let labels = scope
+        .unwrap()
+        .exprs()
+        .iter()
+        .flat_map(|expr| {
+            expr.labels
+                .iter()
+                .map(|label| label.name.as_str().to_owned())
+        })
+        .collect();
+
+    (labels, errors)
+}
+
+#[test]
+fn unmatched_right_paren() {
+    assert_eq!(
+        recover(":a (+ 1 2)) :b 3"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_char(10..11, ')')],
+        )
+    );
+}
+
+#[test]
+fn unclosed_left_paren() {
+    assert_eq!(
+        recover(":a 1 :b (+ 1 2"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_end(14)
+                .expecting(Expected::Char(')'))
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::LabelledExpr)],
+        )
+    );
+}
+
+#[test]
+fn malformed_sexpr() {
+    assert_eq!(
+        recover(":a (+ 1 0x) :b 3"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_char(9..10, 'x')
+                .expecting(Expected::Char(')'))
+                .expecting(Expected::Char(':'))
+                .expecting(Expected::Char('^'))
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::LabelledExpr)],
+        )
+    );
+}
+
+#[test]
+fn malformed_nested_sexpr() {
+    assert_eq!(
+        recover(":a (+ (* 1 0x) 2) :b 3"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_char(12..13, 'x')
+                .expecting(Expected::Char(')'))
+                .expecting(Expected::Char(':'))
+                .expecting(Expected::Char('^'))
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::LabelledExpr)],
+        )
+    );
+}
+
+#[test]
+fn missing_label_name() {
+    assert_eq!(
+        recover(":a (+ 1 :) :b 3"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_char(9..10, ')')
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Label)
+                .with_label(ErrorLabel::LabelledExpr)
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::LabelledExpr)],
+        )
+    );
+}
+
+#[test]
+fn missing_deref_target() {
+    assert_eq!(
+        recover(":a (+ 1 @) :b 3"),
+        (
+            vec!["a".into(), "b".into()],
+            vec![Error::unexpected_char(9..10, ')')
+                .expecting(Expected::Expr)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::Deref)
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::LabelledExpr)],
+        )
+    );
+}
+
+#[test]
+fn one_error_per_mistake() {
+    assert_eq!(
+        recover(":a (+ 1 0x) :b 2) :c 3"),
+        (
+            vec!["a".into(), "b".into(), "c".into()],
+            vec![
+                Error::unexpected_char(9..10, 'x')
+                    .expecting(Expected::Char(')'))
+                    .expecting(Expected::Char(':'))
+                    .expecting(Expected::Char('^'))
+                    .with_label(ErrorLabel::SExpr)
+                    .with_label(ErrorLabel::Reference)
+                    .with_label(ErrorLabel::LabelledExpr),
+                Error::unexpected_char(16..17, ')'),
+            ],
+        )
+    );
+}
+
+#[test]
+fn references_after_mistake_resolve() {
+    assert_eq!(
+        recover(":a (+ 1 0x) :b 3 :c b").1,
+        vec![Error::unexpected_char(9..10, 'x')
+            .expecting(Expected::Char(')'))
+            .expecting(Expected::Char(':'))
+            .expecting(Expected::Char('^'))
+            .with_label(ErrorLabel::SExpr)
+            .with_label(ErrorLabel::Reference)
+            .with_label(ErrorLabel::LabelledExpr)],
+    );
+}
@@ -3,6 +3,7 @@ use pretty_assertions::assert_eq;
 use ari::{
     ast::{Expr, Label, Scope, Symbol},
     parser::{parser, Error, ErrorLabel},
+    prelude::Builtin,
 };
 
 use chumsky::Parser;
@@ -71,7 +72,7 @@ fn nested_self_reference() {
                     [Label::new(0..2, "a")],
                     3..12,
                     Scope::try_from_exprs([
-                        Expr::unresolved_symbol([], 4..5, "+"),
+                        Expr::builtin_reference([], 4..5, 2, Builtin::Add),
                         Expr::resolved_reference([], 6..7, 1, 0, []),
                         Expr::natural([], 8..11, 256u16),
                     ])
@@ -85,6 +86,7 @@ fn nested_self_reference() {
 }
 
 #[test]
+#[allow(clippy::single_range_in_vec_init)]
 fn indirect_self_reference() {
     assert_eq!(
         parser().parse_recovery(":a b :b a"),
@@ -96,7 +98,7 @@ fn indirect_self_reference() {
                 ])
                 .unwrap()
             ),
-            vec![],
+            vec![Error::unguarded_cycle(3..4, [8..9])],
         ),
     );
 }
@@ -113,7 +115,7 @@ fn indirect_unresolved_reference() {
                 ])
                 .unwrap()
             ),
-            vec![],
+            vec![Error::unresolved_symbol(3..4, None)],
         ),
     );
 }
@@ -130,7 +132,7 @@ fn back_reference_in_parent_scope() {
                         [],
                         7..15,
                         Scope::try_from_exprs([
-                            Expr::unresolved_symbol([], 8..9, "*"),
+                            Expr::builtin_reference([], 8..9, 2, Builtin::Multiply),
                             Expr::resolved_reference([Label::new(10..12, "b")], 13..14, 1, -1, []),
                         ])
                         .unwrap()
@@ -154,7 +156,7 @@ fn forward_reference_in_parent_scope() {
                         [],
                         0..8,
                         Scope::try_from_exprs([
-                            Expr::unresolved_symbol([], 1..2, "*"),
+                            Expr::builtin_reference([], 1..2, 2, Builtin::Multiply),
                             Expr::resolved_reference([Label::new(3..5, "a")], 6..7, 1, 1, []),
                         ])
                         .unwrap()
@@ -179,7 +181,7 @@ fn back_reference_in_sibling_scope() {
                         [Label::new(0..2, "a")],
                         3..13,
                         Scope::try_from_exprs([
-                            Expr::unresolved_symbol([], 4..5, "*"),
+                            Expr::builtin_reference([], 4..5, 2, Builtin::Multiply),
                             Expr::natural([Label::new(6..8, "b")], 9..12, 256u16),
                         ])
                         .unwrap()
@@ -205,7 +207,7 @@ fn forward_reference_in_sibling_scope() {
                         [Label::new(7..9, "b")],
                         10..20,
                         Scope::try_from_exprs([
-                            Expr::unresolved_symbol([], 11..12, "*"),
+                            Expr::builtin_reference([], 11..12, 2, Builtin::Multiply),
                             Expr::natural([Label::new(13..15, "a")], 16..19, 256u16),
                         ])
                         .unwrap()
@@ -228,7 +230,7 @@ fn invalid_reference_path() {
                     [],
                     0..17,
                     Scope::try_from_exprs([
-                        Expr::unresolved_symbol([], 1..2, "*"),
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Multiply),
                         Expr::natural([Label::new(3..5, "a")], 6..9, 256u16),
                         Expr::unresolved_reference(
                             [Label::new(10..12, "b")],
@@ -258,17 +260,17 @@ fn partially_invalid_reference_path() {
                     [Label::new(0..2, "a")],
                     3..38,
                     Scope::try_from_exprs([
-                        Expr::unresolved_symbol([], 4..5, "*"),
+                        Expr::builtin_reference([], 4..5, 2, Builtin::Multiply),
                         Expr::sexpr(
                             [Label::new(6..8, "x")],
                             9..26,
                             Scope::try_from_exprs([
-                                Expr::unresolved_symbol([], 10..11, "*"),
+                                Expr::builtin_reference([], 10..11, 3, Builtin::Multiply),
                                 Expr::sexpr(
                                     [Label::new(12..14, "y")],
                                     15..25,
                                     Scope::try_from_exprs([
-                                        Expr::unresolved_symbol([], 16..17, "*"),
+                                        Expr::builtin_reference([], 16..17, 4, Builtin::Multiply),
                                         Expr::natural([Label::new(18..20, "z")], 21..24, 256u16),
                                     ])
                                     .unwrap()
@@ -295,3 +297,22 @@ fn partially_invalid_reference_path() {
         ),
     );
 }
+
+#[test]
+fn scopes_are_shareable() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let (scope, errors) = parser().parse_recovery(":a 1 :a (* a b)");
+    let scope = scope.unwrap();
+    assert_send_sync(&scope);
+    assert!(!errors.is_empty());
+
+    let clone = scope.clone();
+    assert_eq!(clone, scope);
+    assert_eq!(
+        std::thread::spawn(move || clone.exprs().len())
+            .join()
+            .unwrap(),
+        2
+    );
+}
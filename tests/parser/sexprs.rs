@@ -2,7 +2,8 @@ use pretty_assertions::assert_eq;
 
 use ari::{
     ast::{Expr, Label, Scope},
-    parser::{parser, Error, ErrorLabel},
+    parser::{parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
 };
 
 use chumsky::Parser;
@@ -17,7 +18,7 @@ fn sexpr() {
                     [],
                     0..24,
                     Scope::try_from_exprs([
-                        Expr::unresolved_symbol([], 1..2, "*"),
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Multiply),
                         Expr::natural([Label::new(3..5, "r")], 6..9, 256u16),
                         Expr::natural([Label::new(10..12, "g")], 13..16, 256u16),
                         Expr::natural([Label::new(17..19, "b")], 20..23, 256u16),
@@ -69,6 +70,7 @@ fn cant_have_left_paren() {
                     .unwrap()
             ),
             vec![Error::unexpected_end(1)
+                .expecting(Expected::Char(')'))
                 .with_label(ErrorLabel::SExpr)
                 .with_label(ErrorLabel::Reference)],
         )
@@ -81,7 +83,7 @@ fn cant_have_right_paren() {
         parser().parse_recovery(")"),
         (
             Some(Scope::try_from_exprs([]).unwrap()),
-            vec![Error::trailing_garbage(0..1)],
+            vec![Error::unexpected_char(0..1, ')')],
         )
     );
 }
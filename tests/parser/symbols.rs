@@ -2,7 +2,8 @@ use pretty_assertions::assert_eq;
 
 use ari::{
     ast::{Expr, Scope},
-    parser::{parser, Error, ErrorLabel},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
 };
 
 use chumsky::Parser;
@@ -10,7 +11,7 @@ use chumsky::Parser;
 #[test]
 fn symbol() {
     assert_eq!(
-        parser().parse_recovery("symbol"),
+        open_parser().parse_recovery("symbol"),
         (
             Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..6, "symbol")]).unwrap()),
             vec![],
@@ -36,14 +37,16 @@ fn supports_builtin_fn_names() {
         .into_iter()
         .map(|symbol| {
             (
-                parser().parse_recovery(symbol),
+                open_parser().parse_recovery(symbol),
                 (
                     Some(
-                        Scope::try_from_exprs([Expr::unresolved_symbol(
-                            [],
-                            0..symbol.len(),
-                            symbol,
-                        )])
+                        Scope::try_from_exprs([Builtin::ALL
+                            .into_iter()
+                            .find(|builtin| builtin.names().contains(&symbol))
+                            .map_or_else(
+                                || Expr::unresolved_symbol([], 0..symbol.len(), symbol),
+                                |builtin| Expr::builtin_reference([], 0..symbol.len(), 1, builtin),
+                            )])
                         .unwrap(),
                     ),
                     vec![],
@@ -58,7 +61,7 @@ fn supports_builtin_fn_names() {
 #[test]
 fn supports_almost_all_of_unicode_with_exceptions() {
     assert_eq!(
-        parser().parse_recovery("🙃"),
+        open_parser().parse_recovery("🙃"),
         (
             Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..1, "🙃")]).unwrap()),
             vec![]
@@ -69,7 +72,7 @@ fn supports_almost_all_of_unicode_with_exceptions() {
 #[test]
 fn cant_have_whitespace() {
     assert_eq!(
-        parser().parse_recovery("symbol "),
+        open_parser().parse_recovery("symbol "),
         (
             Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..6, "symbol")]).unwrap()),
             vec![],
@@ -84,6 +87,8 @@ fn cant_have_colon() {
         (
             Some(Scope::try_from_exprs([]).unwrap()),
             vec![Error::unexpected_end(7)
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
                 .with_label(ErrorLabel::Symbol)
                 .with_label(ErrorLabel::Label)
                 .with_label(ErrorLabel::Path)
@@ -95,7 +100,7 @@ fn cant_have_colon() {
 #[test]
 fn cant_have_left_paren() {
     assert_eq!(
-        parser().parse_recovery("symbol("),
+        open_parser().parse_recovery("symbol("),
         (
             Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..6, "symbol")]).unwrap()),
             vec![Error::trailing_garbage(6..7)],
@@ -106,10 +111,10 @@ fn cant_have_left_paren() {
 #[test]
 fn cant_have_right_paren() {
     assert_eq!(
-        parser().parse_recovery("symbol)"),
+        open_parser().parse_recovery("symbol)"),
         (
             Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..6, "symbol")]).unwrap()),
-            vec![Error::trailing_garbage(6..7)],
+            vec![Error::unexpected_char(6..7, ')')],
         ),
     );
 }
@@ -0,0 +1,147 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, ExprVariant, Label, Scope},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
+    text::{EncodedText, Encoding},
+};
+
+use chumsky::Parser;
+
+#[test]
+fn text() {
+    assert_eq!(
+        parser().parse_recovery("\"()\""),
+        (
+            Some(Scope::try_from_exprs([Expr::text([], 0..4, "()")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn empty() {
+    assert_eq!(
+        parser().parse_recovery("\"\""),
+        (
+            Some(Scope::try_from_exprs([Expr::text([], 0..2, "")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn escaped_quotes() {
+    assert_eq!(
+        parser().parse_recovery("\"{\n  \"\"abc\"\": 123\n}\""),
+        (
+            Some(Scope::try_from_exprs([Expr::text([], 0..20, "{\n  \"abc\": 123\n}")]).unwrap()),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn labelled() {
+    assert_eq!(
+        parser().parse_recovery(":terminal \"terminal\""),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::text(
+                    [Label::new(0..9, "terminal")],
+                    10..20,
+                    "terminal"
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn in_sexpr() {
+    assert_eq!(
+        open_parser().parse_recovery("(| \"ab\" (* \"cd\" x))"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..19,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Union),
+                        Expr::text([], 3..7, "ab"),
+                        Expr::sexpr(
+                            [],
+                            8..18,
+                            Scope::try_from_exprs([
+                                Expr::builtin_reference([], 9..10, 3, Builtin::Multiply),
+                                Expr::text([], 11..15, "cd"),
+                                Expr::unresolved_symbol([], 16..17, "x"),
+                            ])
+                            .unwrap()
+                        ),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn encoding_context() {
+    let scope = parser().parse("(utf-16 \"ab\")").unwrap();
+    let [expr] = scope.exprs() else {
+        panic!("expected a single expression")
+    };
+
+    let ExprVariant::SExpr(sexpr) = &expr.base.variant else {
+        panic!("expected a symbolic expression")
+    };
+
+    assert_eq!(
+        sexpr.exprs().first(),
+        Some(&Expr::builtin_reference(
+            [],
+            1..7,
+            2,
+            Builtin::Encoding(Encoding::Utf16)
+        ))
+    );
+    assert_eq!(
+        Encoding::Utf16.encode("ab"),
+        Ok(EncodedText {
+            encoding: Encoding::Utf16,
+            code_units: Box::new([0x61, 0x62]),
+        })
+    );
+}
+
+#[test]
+fn cant_apply_path() {
+    assert_eq!(
+        parser().parse_recovery("\"ab\":x"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![Error::invalid_path(4..6).with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn must_be_terminated() {
+    assert_eq!(
+        parser().parse_recovery("\"abc"),
+        (
+            Some(Scope::try_from_exprs([Expr::text([], 0..4, "abc")]).unwrap()),
+            vec![Error::unexpected_end(4)
+                .expecting(Expected::Char('"'))
+                .with_label(ErrorLabel::Text)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
@@ -2,7 +2,8 @@ use pretty_assertions::assert_eq;
 
 use ari::{
     ast::{Expr, Label, Scope, Symbol},
-    parser::{parser, Error, ErrorLabel},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
 };
 
 use chumsky::Parser;
@@ -21,7 +22,7 @@ fn apply_path_to_symbol() {
                 )])
                 .unwrap()
             ),
-            vec![],
+            vec![Error::unresolved_symbol(0..6, None)],
         )
     );
 }
@@ -44,7 +45,7 @@ fn apply_deep_path_to_symbol() {
                 )])
                 .unwrap()
             ),
-            vec![],
+            vec![Error::unresolved_symbol(0..6, None)],
         )
     );
 }
@@ -67,6 +68,8 @@ fn path_must_be_complete() {
         (
             Some(Scope::try_from_exprs([]).unwrap()),
             vec![Error::unexpected_end(7)
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
                 .with_label(ErrorLabel::Symbol)
                 .with_label(ErrorLabel::Label)
                 .with_label(ErrorLabel::Path)
@@ -89,7 +92,12 @@ fn deep_path_must_be_chained() {
                 )])
                 .unwrap()
             ),
-            vec![Error::unexpected_end(13).with_label(ErrorLabel::LabelledExpr)],
+            vec![
+                Error::unexpected_end(13)
+                    .expecting(Expected::Expr)
+                    .with_label(ErrorLabel::LabelledExpr),
+                Error::unresolved_symbol(0..6, None),
+            ],
         )
     );
 }
@@ -102,6 +110,8 @@ fn path_cant_have_left_paren() {
             Some(Scope::try_from_exprs([]).unwrap()),
             vec![
                 Error::unexpected_char(7..8, '(')
+                    .expecting(Expected::Char('\''))
+                    .expecting(Expected::Symbol)
                     .with_label(ErrorLabel::Symbol)
                     .with_label(ErrorLabel::Label)
                     .with_label(ErrorLabel::Path)
@@ -118,14 +128,13 @@ fn path_cant_have_right_paren() {
         parser().parse_recovery("symbol:)"),
         (
             Some(Scope::try_from_exprs([]).unwrap()),
-            vec![
-                Error::unexpected_char(7..8, ')')
-                    .with_label(ErrorLabel::Symbol)
-                    .with_label(ErrorLabel::Label)
-                    .with_label(ErrorLabel::Path)
-                    .with_label(ErrorLabel::Reference),
-                Error::trailing_garbage(7..8),
-            ],
+            vec![Error::unexpected_char(7..8, ')')
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Symbol)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Label)
+                .with_label(ErrorLabel::Path)
+                .with_label(ErrorLabel::Reference)],
         )
     );
 }
@@ -162,3 +171,75 @@ fn cant_apply_path_to_missing_path_label_in_sexpr() {
         )
     );
 }
+
+#[test]
+fn suggests_label_in_scope() {
+    assert_eq!(
+        parser().parse_recovery(":size 3 (* sze 2)"),
+        (
+            Some(
+                Scope::try_from_exprs([
+                    Expr::natural([Label::new(0..5, "size")], 6..7, 3u8),
+                    Expr::sexpr(
+                        [],
+                        8..17,
+                        Scope::try_from_exprs([
+                            Expr::builtin_reference([], 9..10, 2, Builtin::Multiply),
+                            Expr::unresolved_symbol([], 11..14, "sze"),
+                            Expr::natural([], 15..16, 2u8),
+                        ])
+                        .unwrap()
+                    ),
+                ])
+                .unwrap()
+            ),
+            vec![Error::unresolved_symbol(11..14, Some("size".to_owned()))],
+        )
+    );
+}
+
+#[test]
+fn suggests_builtin() {
+    assert_eq!(
+        parser().parse_recovery("(utf16 \"ab\")"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..12,
+                    Scope::try_from_exprs([
+                        Expr::unresolved_symbol([], 1..6, "utf16"),
+                        Expr::text([], 7..11, "ab"),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![Error::unresolved_symbol(1..6, Some("utf-16".to_owned()))],
+        )
+    );
+}
+
+#[test]
+fn reports_every_unresolved_reference_in_order() {
+    assert_eq!(
+        parser().parse_recovery("(+ x @y) $z").1,
+        vec![
+            Error::invalid_value_ref(9..11),
+            Error::unresolved_symbol(3..4, None),
+            Error::unresolved_symbol(6..7, None),
+            Error::unresolved_symbol(10..11, None),
+        ],
+    );
+}
+
+#[test]
+fn open_scopes_keep_unresolved_references() {
+    assert_eq!(
+        open_parser().parse_recovery("symbol"),
+        (
+            Some(Scope::try_from_exprs([Expr::unresolved_symbol([], 0..6, "symbol")]).unwrap()),
+            vec![],
+        )
+    );
+}
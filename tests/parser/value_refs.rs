@@ -0,0 +1,186 @@
+use pretty_assertions::assert_eq;
+
+use ari::{
+    ast::{Expr, Label, Scope, Symbol},
+    parser::{open_parser, parser, Error, ErrorLabel, Expected},
+    prelude::Builtin,
+};
+
+use chumsky::Parser;
+
+#[test]
+fn in_product() {
+    assert_eq!(
+        parser().parse_recovery("(* :a 256 $a)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..13,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Multiply),
+                        Expr::natural([Label::new(3..5, "a")], 6..9, 256u16),
+                        Expr::resolved_value_ref([], 10..12, 0, -1, []),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn in_map_base() {
+    assert_eq!(
+        open_parser().parse_recovery("(^ $a 256)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..10,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Exponentiate),
+                        Expr::unresolved_value_ref([], 3..5, Symbol::new(4..5, "a"), []),
+                        Expr::natural([], 6..9, 256u16),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn with_path() {
+    assert_eq!(
+        open_parser().parse_recovery("(* $a:b)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..8,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Multiply),
+                        Expr::unresolved_value_ref(
+                            [],
+                            3..7,
+                            Symbol::new(4..5, "a"),
+                            [Label::new(5..7, "b")]
+                        ),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![],
+        )
+    );
+}
+
+#[test]
+fn cant_be_in_root() {
+    assert_eq!(
+        open_parser().parse_recovery("$a"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::unresolved_value_ref(
+                    [],
+                    0..2,
+                    Symbol::new(1..2, "a"),
+                    []
+                )])
+                .unwrap()
+            ),
+            vec![Error::invalid_value_ref(0..2)],
+        )
+    );
+}
+
+#[test]
+fn cant_be_in_sum() {
+    assert_eq!(
+        open_parser().parse_recovery("(+ 1 $a)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..8,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Add),
+                        Expr::natural([], 3..4, 1u8),
+                        Expr::unresolved_value_ref([], 5..7, Symbol::new(6..7, "a"), []),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![Error::invalid_value_ref(5..7)
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn cant_be_in_map_exponent() {
+    assert_eq!(
+        open_parser().parse_recovery("(^ 2 $a)"),
+        (
+            Some(
+                Scope::try_from_exprs([Expr::sexpr(
+                    [],
+                    0..8,
+                    Scope::try_from_exprs([
+                        Expr::builtin_reference([], 1..2, 2, Builtin::Exponentiate),
+                        Expr::natural([], 3..4, 2u8),
+                        Expr::unresolved_value_ref([], 5..7, Symbol::new(6..7, "a"), []),
+                    ])
+                    .unwrap()
+                )])
+                .unwrap()
+            ),
+            vec![Error::invalid_value_ref(5..7)
+                .with_label(ErrorLabel::SExpr)
+                .with_label(ErrorLabel::Reference)],
+        )
+    );
+}
+
+#[test]
+fn cant_be_dereferenced() {
+    assert_eq!(
+        parser().parse_recovery("@$a"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![
+                Error::unexpected_char(1..2, '$')
+                    .expecting(Expected::Expr)
+                    .with_label(ErrorLabel::Symbol)
+                    .with_label(ErrorLabel::Reference)
+                    .with_label(ErrorLabel::Deref),
+                Error::trailing_garbage(1..3),
+            ],
+        )
+    );
+}
+
+#[test]
+fn must_have_symbol() {
+    assert_eq!(
+        parser().parse_recovery("$"),
+        (
+            Some(Scope::try_from_exprs([]).unwrap()),
+            vec![Error::unexpected_end(1)
+                .expecting(Expected::Char('\''))
+                .expecting(Expected::Char('\\'))
+                .expecting(Expected::Symbol)
+                .with_label(ErrorLabel::Symbol)
+                .with_label(ErrorLabel::Reference)
+                .with_label(ErrorLabel::ValueRef)],
+        )
+    );
+}